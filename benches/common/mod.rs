@@ -0,0 +1,110 @@
+// Tiny timing harness shared by the benches, they're plain binaries (harness = false) so they
+// run anywhere `cargo bench` does, without SDL or any extra crate.
+// Every bench binary compiles its own copy and only uses part of it
+#![allow(dead_code)]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// How long each benchmark keeps running once warmed up
+const MEASURE_TIME: Duration = Duration::from_millis(500);
+const WARM_UP_ITERATIONS: u32 = 10;
+
+// Benchmark names to run, from the command line like libtest filters,
+// e.g. `cargo bench --bench geometry -- holds`. No filter runs everything.
+pub struct Bencher {
+    filters: Vec<String>,
+}
+
+impl Bencher {
+    pub fn from_args() -> Bencher {
+        // cargo passes `--bench` along, flags aren't filters
+        let filters = std::env::args()
+            .skip(1)
+            .filter(|arg| !arg.starts_with("--"))
+            .collect();
+
+        return Bencher { filters };
+    }
+
+    fn skipped(&self, name: &str) -> bool {
+        return !self.filters.is_empty() && !self.filters.iter().any(|filter| name.contains(filter.as_str()));
+    }
+
+    // Times `f` and prints how long one call takes on average
+    pub fn bench<R>(&self, name: &str, mut f: impl FnMut() -> R) {
+        if self.skipped(name) {
+            return;
+        }
+
+        for _ in 0..WARM_UP_ITERATIONS {
+            black_box(f());
+        }
+
+        let mut iterations: u64 = 0;
+        let mut batch: u64 = 1;
+        let started = Instant::now();
+        while started.elapsed() < MEASURE_TIME {
+            for _ in 0..batch {
+                black_box(f());
+            }
+            iterations += batch;
+            batch *= 2;
+        }
+
+        report(name, started.elapsed(), iterations);
+    }
+
+    // Same as bench, `f` gets the inputs one after the other, starting over from the first one
+    // once they've all been used. They go through black_box so the calls can't be worked out
+    // at compile time
+    pub fn bench_inputs<T, R>(&self, name: &str, inputs: &[T], mut f: impl FnMut(&T) -> R) {
+        let mut input = 0;
+        self.bench(name, || {
+            let value = black_box(&inputs[input]);
+            input = (input + 1) % inputs.len();
+            f(value)
+        });
+    }
+
+    // For benches changing what they run on, like a game tick: every call of `f` gets a fresh
+    // value from `setup`, so they all measure the same thing. Only `f` is timed
+    pub fn bench_with_setup<S, R>(&self, name: &str, mut setup: impl FnMut() -> S, mut f: impl FnMut(&mut S) -> R) {
+        if self.skipped(name) {
+            return;
+        }
+
+        for _ in 0..WARM_UP_ITERATIONS {
+            let mut value = black_box(setup());
+            black_box(f(&mut value));
+        }
+
+        let mut iterations: u64 = 0;
+        let mut measured = Duration::ZERO;
+        while measured < MEASURE_TIME {
+            let mut value = black_box(setup());
+            let started = Instant::now();
+            black_box(f(&mut value));
+            measured += started.elapsed();
+            iterations += 1;
+        }
+
+        report(name, measured, iterations);
+    }
+}
+
+fn report(name: &str, elapsed: Duration, iterations: u64) {
+    let nanos = elapsed.as_nanos() as f64 / iterations as f64;
+    println!("{:<56} {:>12} per iteration ({} iterations)", name, format_nanos(nanos), iterations);
+}
+
+fn format_nanos(nanos: f64) -> String {
+    if nanos < 10_000.0 {
+        return format!("{:.1} ns", nanos);
+    }
+    if nanos < 10_000_000.0 {
+        return format!("{:.2} us", nanos / 1_000.0);
+    }
+
+    return format!("{:.2} ms", nanos / 1_000_000.0);
+}
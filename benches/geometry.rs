@@ -0,0 +1,69 @@
+// The geometry helpers the simulation and the culling call the most:
+//   cargo bench --no-default-features --bench geometry [-- filter]
+// See benches/BASELINE.md for reference numbers.
+extern crate agario;
+
+mod common;
+
+use std::hint::black_box;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use agario::geometry::{Position, circle::Circle, shape::Shape, rectangle::{Rectangle, RectangleSize, Size}};
+
+// Inputs are cycled through so the branches aren't always taken the same way, see Bencher::bench_inputs
+const INPUTS: usize = 1024;
+
+fn positions(rng: &mut StdRng) -> Vec<Position> {
+    return (0..INPUTS)
+        .map(|_| Position {
+            x: rng.gen_range(-1000.0..1000.0),
+            y: rng.gen_range(-1000.0..1000.0),
+        })
+        .collect();
+}
+
+fn main() {
+    let bencher = common::Bencher::from_args();
+    let mut rng = StdRng::seed_from_u64(1);
+    let targets = positions(&mut rng);
+    let circles: Vec<Circle> = positions(&mut rng)
+        .into_iter()
+        .map(|center| Circle {
+            center,
+            radius: rng.gen_range(1..500),
+        })
+        .collect();
+    let rectangle = Rectangle {
+        position: Position { x: 0.0, y: 0.0 },
+        size: Size::Rectangle(RectangleSize {
+            width: 800,
+            height: 600,
+        }),
+    };
+
+    let pairs: Vec<(Circle, Position)> = circles.iter().copied().zip(targets.iter().copied()).collect();
+    let outside: Vec<Position> = targets.iter().map(|target| *target * 3.0).collect();
+    let inside: Vec<Position> = targets.iter().map(|target| *target / 4.0).collect();
+    let circle_pairs: Vec<(Circle, Circle)> = circles.iter().copied().zip(circles.iter().copied().cycle().skip(1)).collect();
+    let rectangle = black_box(&rectangle);
+
+    bencher.bench_inputs("Circle::holds", &pairs, |(circle, target)| circle.holds(*target));
+
+    bencher.bench_inputs("Position::get_distance", &pairs, |(circle, target)| circle.center.get_distance(*target));
+
+    bencher.bench_inputs("Rectangle::closest_position_within (outside)", &outside, |target| {
+        rectangle.closest_position_within(*target)
+    });
+
+    bencher.bench_inputs("Rectangle::closest_position_within (inside)", &inside, |target| {
+        rectangle.closest_position_within(*target)
+    });
+
+    bencher.bench_inputs("Shape::intersects (circle, rectangle)", &circles, |circle| rectangle.intersects(circle));
+
+    bencher.bench_inputs("Shape::overlap_area (circle, rectangle)", &circles, |circle| rectangle.overlap_area(circle));
+
+    bencher.bench_inputs("Shape::overlap_area (circle, circle)", &circle_pairs, |(a, b)| a.overlap_area(b));
+}
@@ -0,0 +1,111 @@
+// Times the movement and collision systems on one thread against several, on worlds of
+// 1k, 10k and 100k entities, and checks both end up with the same world:
+//   cargo bench --no-default-features --bench parallel [-- threads]
+// The thread count defaults to every core.
+extern crate agario;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use agario::{settings, time};
+use agario::geometry::{Position, circle::Circle};
+use agario::game::arena::Arena;
+use agario::game::ecs::{World, components::Velocity, parallel, systems::{collision, movement}};
+use agario::game::ids::{Entity, PlayerId};
+use agario::game::player::{JoinRequest, Player};
+
+const ENTITIES: [usize; 3] = [1_000, 10_000, 100_000];
+const TICKS: u32 = 20;
+// Both are timed this many times, taking turns, and the fastest run of each is kept
+const ROUNDS: u32 = 5;
+
+// A quarter of the entities are body parts of as many bots, drifting around, the rest fruits
+fn world(entities: usize) -> (World, Arena<PlayerId, Player>) {
+    let mut rng = StdRng::seed_from_u64(entities as u64);
+    let mut players = Arena::new();
+    let mut world = World::new(settings::MAP_WIDTH, settings::MAP_HEIGHT);
+    let random_circle = |rng: &mut StdRng, radius: u32| Circle {
+        center: Position {
+            x: rng.gen_range(0.0..settings::MAP_WIDTH as f32),
+            y: rng.gen_range(0.0..settings::MAP_HEIGHT as f32),
+        },
+        radius,
+    };
+
+    for number in 0..entities / 4 {
+        let player_id = players.insert_with(|player_id| Player::new(player_id, JoinRequest::bot(number)));
+        let radius = rng.gen_range(settings::INITIAL_PLAYER_SCORE..60);
+        let body_part = world.spawn_cell(player_id, random_circle(&mut rng, radius));
+        world.velocities.insert(body_part, Velocity(Position {
+            x: rng.gen_range(-2.0..2.0),
+            y: rng.gen_range(-2.0..2.0),
+        }));
+
+        if let Some(player) = players.get_mut(player_id) {
+            player.body_parts.push(body_part);
+        }
+    }
+
+    while world.len() < entities {
+        world.spawn_fruit(random_circle(&mut rng, settings::FRUIT_RADIUS));
+    }
+
+    return (world, players);
+}
+
+// Average nanoseconds per tick, and the world after the last one
+fn run(world: &World, players: &Arena<PlayerId, Player>, threads: usize) -> (u128, Vec<(Entity, Circle)>) {
+    let mut world = world.clone();
+    let started = time::now();
+
+    for _ in 0..TICKS {
+        movement::run(&mut world, threads);
+        collision::run(&mut world, players, threads);
+    }
+
+    let average = (time::now() - started) / TICKS as u128;
+    let outcome = world
+        .entities()
+        .filter_map(|entity| Some((entity, world.circle(entity)?)))
+        .collect();
+
+    return (average, outcome);
+}
+
+fn same(a: &[(Entity, Circle)], b: &[(Entity, Circle)]) -> bool {
+    return a.len() == b.len() && a.iter().zip(b).all(|((a_entity, a), (b_entity, b))| {
+        a_entity == b_entity && a.center.x == b.center.x && a.center.y == b.center.y && a.radius == b.radius
+    });
+}
+
+fn main() {
+    let threads = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or_else(parallel::default_threads);
+
+    println!("Movement and collision, average of {} ticks, fastest of {} runs, {} threads against 1", TICKS, ROUNDS, threads);
+    println!("{:>9} {:>12} {:>12} {:>8}", "Entities", "Sequential", "Parallel", "Speedup");
+
+    for entities in ENTITIES {
+        let (world, players) = world(entities);
+        let (mut sequential, mut parallel) = (u128::MAX, u128::MAX);
+
+        for _ in 0..ROUNDS {
+            let (sequential_time, sequential_outcome) = run(&world, &players, 1);
+            let (parallel_time, parallel_outcome) = run(&world, &players, threads);
+            assert!(same(&sequential_outcome, &parallel_outcome), "the parallel systems changed the outcome");
+
+            sequential = sequential.min(sequential_time);
+            parallel = parallel.min(parallel_time);
+        }
+
+        println!(
+            "{:>9} {:>10.3}ms {:>10.3}ms {:>7.2}x",
+            entities,
+            sequential as f64 / 1_000_000.0,
+            parallel as f64 / 1_000_000.0,
+            sequential as f64 / parallel.max(1) as f64,
+        );
+    }
+}
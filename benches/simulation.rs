@@ -0,0 +1,74 @@
+// Whole simulation ticks and the culling queries the client runs every frame:
+//   cargo bench --no-default-features --bench simulation [-- filter]
+// See benches/BASELINE.md for reference numbers.
+extern crate agario;
+
+mod common;
+
+use std::hint::black_box;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use agario::settings;
+use agario::game::{Game, Mode, fruit::Fruit, map::MapView, player::JoinRequest};
+use agario::geometry::{Position, rectangle::{RectangleSize, Size}};
+
+// (bots, fruits), the first one is what a fresh game starts with
+const WORLDS: [(usize, usize); 4] = [(10, 600), (100, 600), (100, 5_000), (500, 20_000)];
+
+// Every tick runs on a fresh clone of the same game, so they all start from the counts in the name
+fn game(bots: usize, fruits: usize) -> Game {
+    let mut rng = StdRng::seed_from_u64((bots * fruits) as u64);
+    let mut game = Game::new(Mode::Multiplayer);
+    // Timings shouldn't depend on the cores of the machine
+    game.threads = 1;
+
+    for number in 1..=bots {
+        game.add_player(JoinRequest::bot(number));
+    }
+    for _ in settings::FRUITS as usize..fruits {
+        game.map.world.spawn_fruit(Fruit::new(&mut rng, game.map.width, game.map.height));
+    }
+
+    return game;
+}
+
+// A full HD screen in the middle of the map
+fn view() -> MapView {
+    return MapView {
+        position: Position {
+            x: settings::MAP_WIDTH as f32 / 2.0,
+            y: settings::MAP_HEIGHT as f32 / 2.0,
+        },
+        size: Size::Rectangle(RectangleSize {
+            width: 1920,
+            height: 1080,
+        }),
+    };
+}
+
+fn main() {
+    let bencher = common::Bencher::from_args();
+
+    for (bots, fruits) in WORLDS {
+        let game = game(bots, fruits);
+        bencher.bench_with_setup(
+            &format!("Game::update ({} bots, {} fruits)", bots, fruits),
+            || game.clone(),
+            |game| game.update(),
+        );
+    }
+
+    let view = black_box(view());
+    for (bots, fruits) in WORLDS {
+        let game = game(bots, fruits);
+
+        bencher.bench(&format!("MapView::get_visible_fruits ({} bots, {} fruits)", bots, fruits), || {
+            view.get_visible_fruits(black_box(&game.map))
+        });
+        bencher.bench(&format!("MapView::get_visible_players ({} bots, {} fruits)", bots, fruits), || {
+            view.get_visible_players(black_box(&game.map))
+        });
+    }
+}
@@ -0,0 +1,51 @@
+extern crate agario;
+
+use std::io::BufRead;
+use std::sync::mpsc;
+
+use agario::{settings, config::Config, logging, info, error};
+use agario::game::{Game, Mode};
+use agario::helper::{G2UMessage, U2GMessage};
+
+// Runs the game without a window, commands are read from the terminal instead
+fn main() {
+    let (g2u_tx, g2u_rx) = mpsc::channel::<G2UMessage>();
+    // Nobody sends on it without a window, but it stays open so the game keeps running
+    let (_u2g_tx, u2g_rx) = mpsc::channel::<U2GMessage>();
+
+    let config = Config::load(settings::CONFIG_FILE_PATH);
+
+    logging::init(&config);
+    info!("starting", binary = "server", config = settings::CONFIG_FILE_PATH);
+
+    let mut game = Game::new(Mode::Multiplayer);
+    game.apply_config(&config);
+
+    // Lines typed in the terminal go through their own channel, only those may run any command
+    let (console_tx, console_rx) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            if console_tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    std::thread::spawn(move || {
+        for message in g2u_rx.iter() {
+            if let G2UMessage::CommandOutput(_, text) = message {
+                println!("{}", text);
+            }
+        }
+    });
+
+    if let Err(error) = game.init_with_console(&g2u_tx, &u2g_rx, &console_rx) {
+        error!("game failed", error = error);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,101 @@
+extern crate agario;
+
+use agario::{settings, config::Config, logging};
+use std::collections::BTreeMap;
+
+use agario::game::{Game, Mode, events::GameEvent, player::JoinRequest};
+
+const USAGE: &str = "\
+Usage: agario-tools <command>
+
+Commands:
+  simulate [ticks] [bots]  Runs the simulation as fast as it can and reports the tick timings
+                           and how many of each game event happened
+  help                     Shows this text";
+
+// Offline tools built on top of the simulation library
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::load(settings::CONFIG_FILE_PATH);
+    logging::init(&config);
+
+    let result = match args.first().map(|command| command.as_str()) {
+        Some("simulate") => simulate(&args[1..], &config),
+        Some("help") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("Unknown command {}\n\n{}", command, USAGE)),
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+fn parse_argument(args: &[String], index: usize, default: u64) -> Result<u64, String> {
+    match args.get(index) {
+        Some(value) => value.parse().map_err(|_| format!("Expected a number, got {}", value)),
+        None => Ok(default),
+    }
+}
+
+fn simulate(args: &[String], config: &Config) -> Result<(), String> {
+    let ticks = parse_argument(args, 0, 1000)?;
+    let bots = parse_argument(args, 1, 50)?;
+
+    let mut game = Game::new(Mode::Multiplayer);
+    game.apply_config(config);
+
+    let events = game.subscribe();
+
+    for number in 1..=bots as usize {
+        game.add_player(JoinRequest::bot(number));
+    }
+
+    let mut total = 0;
+    let mut slowest = 0;
+    let mut event_counts: BTreeMap<&str, u64> = BTreeMap::new();
+    for _ in 0..ticks {
+        game.update();
+        total += game.timings.tick;
+        slowest = slowest.max(game.timings.tick);
+
+        for tick_events in events.try_iter() {
+            for event in tick_events.events {
+                *event_counts.entry(event_name(&event)).or_default() += 1;
+            }
+        }
+    }
+
+    let average = total / ticks.max(1) as u128;
+    println!("Simulated {} ticks with {} bots", ticks, bots);
+    println!("Average tick: {}us", average / 1000);
+    println!("Slowest tick: {}us", slowest / 1000);
+    println!("Max tick rate: {}", match average {
+        0 => String::from("unbounded"),
+        average => (1_000_000_000 / average).to_string(),
+    });
+    println!("Fruits left: {}", game.map.world.fruits().count());
+    println!("Players left: {}", game.map.players.len());
+
+    println!("Events:");
+    for (name, count) in event_counts {
+        println!("  {}: {}", name, count);
+    }
+
+    return Ok(());
+}
+
+fn event_name(event: &GameEvent) -> &'static str {
+    match event {
+        GameEvent::PlayerJoined { .. } => "PlayerJoined",
+        GameEvent::PlayerLeft { .. } => "PlayerLeft",
+        GameEvent::FruitEaten { .. } => "FruitEaten",
+        GameEvent::CellEaten { .. } => "CellEaten",
+        GameEvent::PlayerDied { .. } => "PlayerDied",
+        GameEvent::Split { .. } => "Split",
+        GameEvent::Merge { .. } => "Merge",
+    }
+}
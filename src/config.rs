@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Values read from the config file, keyed as "section.key".
+// The file is a list of `key = value` lines grouped under `[section]` headers,
+// lines starting with `#` are comments.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Config {
+        match fs::read_to_string(Path::new(path)) {
+            Ok(content) => Config::parse(&content),
+            Err(_) => Config::default(),
+        }
+    }
+
+    pub fn parse(content: &str) -> Config {
+        let mut values = HashMap::new();
+        let mut section = String::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let key = match section.is_empty() {
+                    true => key.trim().to_string(),
+                    false => format!("{}.{}", section, key.trim()),
+                };
+                values.insert(key, value.trim().to_string());
+            }
+        }
+
+        return Config {
+            values,
+        };
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    // Comma separated values, e.g. `up = W, Up`
+    pub fn get_list(&self, key: &str) -> Option<Vec<&str>> {
+        self.get(key).map(|value| {
+            value
+                .split(',')
+                .map(|item| item.trim())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+    }
+
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get(key).and_then(|value| value.parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keys_by_section() {
+        let config = Config::parse("
+            # a comment
+            top = level
+
+            [input]
+            up = W, Up
+              split=Space  
+            [ game ]
+            tick_rate = 300
+            not a key value line
+        ");
+
+        assert_eq!(config.get("top"), Some("level"));
+        assert_eq!(config.get("input.up"), Some("W, Up"));
+        assert_eq!(config.get("input.split"), Some("Space"));
+        assert_eq!(config.get("game.tick_rate"), Some("300"));
+        assert_eq!(config.get("tick_rate"), None);
+        assert_eq!(config.get("# a comment"), None);
+    }
+
+    #[test]
+    fn later_values_win_and_empty_values_stay() {
+        let config = Config::parse("[console]\noperator_tokens = a\noperator_tokens =\n[log]\nlevel = debug = trace");
+
+        assert_eq!(config.get("console.operator_tokens"), Some(""));
+        assert_eq!(config.get_list("console.operator_tokens"), Some(vec![]));
+        // Only the first `=` splits
+        assert_eq!(config.get("log.level"), Some("debug = trace"));
+    }
+
+    #[test]
+    fn lists_and_numbers() {
+        let config = Config::parse("[input]\nup = W, , Up ,\ndead_zone = 0.25\nbroken = fast");
+
+        assert_eq!(config.get_list("input.up"), Some(vec!["W", "Up"]));
+        assert_eq!(config.get_list("input.down"), None);
+        assert_eq!(config.get_f32("input.dead_zone"), Some(0.25));
+        assert_eq!(config.get_f32("input.broken"), None);
+        assert_eq!(config.get_f32("input.missing"), None);
+    }
+}
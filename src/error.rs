@@ -0,0 +1,61 @@
+use std::fmt;
+use std::sync::mpsc::SendError;
+
+use crate::helper::{G2UMessage, U2GMessage};
+
+// The game and the UI talk over channels, either end may go away at any time
+#[derive(Debug)]
+pub enum TransportError {
+    GameDisconnected,
+    UiDisconnected,
+}
+
+#[derive(Debug)]
+pub enum GameError {
+    Transport(TransportError),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransportError::GameDisconnected => write!(f, "the game stopped responding"),
+            TransportError::UiDisconnected => write!(f, "the window was closed"),
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Transport(error) => write!(f, "Game stopped, {}", error),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+impl std::error::Error for GameError {}
+
+// Messages to the game only fail once the game is gone, and the other way around
+impl From<SendError<U2GMessage>> for TransportError {
+    fn from(_: SendError<U2GMessage>) -> TransportError {
+        TransportError::GameDisconnected
+    }
+}
+
+impl From<SendError<G2UMessage>> for TransportError {
+    fn from(_: SendError<G2UMessage>) -> TransportError {
+        TransportError::UiDisconnected
+    }
+}
+
+impl From<TransportError> for GameError {
+    fn from(error: TransportError) -> GameError {
+        GameError::Transport(error)
+    }
+}
+
+impl From<SendError<G2UMessage>> for GameError {
+    fn from(error: SendError<G2UMessage>) -> GameError {
+        GameError::Transport(error.into())
+    }
+}
@@ -0,0 +1,275 @@
+use std::marker::PhantomData;
+
+// Where a value lives in an arena: its slot and the generation of that slot.
+// A slot gets a new generation every time it's freed, so the index of a removed
+// value never points at whatever is stored there next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Index {
+    pub slot: u32,
+    pub generation: u32,
+}
+
+// Typed wrapper around an Index, see game::ids
+pub trait Key: Copy {
+    fn from_index(index: Index) -> Self;
+    fn index(&self) -> Index;
+}
+
+#[derive(Clone, Debug)]
+enum Entry<T> {
+    Occupied {
+        generation: u32,
+        value: T,
+    },
+    Free {
+        generation: u32,
+    },
+}
+
+// Vec backed storage handing out generational keys, lookups are a bounds and a generation check.
+// Iterates in slot order, which unlike a HashMap is the same on every run.
+#[derive(Clone, Debug)]
+pub struct Arena<K, T> {
+    entries: Vec<Entry<T>>,
+    // Free slots, the last freed one is reused first
+    free: Vec<u32>,
+    len: usize,
+    key: PhantomData<K>,
+}
+
+impl<K: Key, T> Default for Arena<K, T> {
+    fn default() -> Arena<K, T> {
+        return Arena::new();
+    }
+}
+
+impl<K: Key, T> Arena<K, T> {
+    pub fn new() -> Arena<K, T> {
+        return Arena {
+            entries: vec![],
+            free: vec![],
+            len: 0,
+            key: PhantomData,
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    pub fn insert(&mut self, value: T) -> K {
+        return self.insert_with(|_| value);
+    }
+
+    // For values that need to know their own key
+    pub fn insert_with(&mut self, create: impl FnOnce(K) -> T) -> K {
+        self.len += 1;
+
+        if let Some(slot) = self.free.pop() {
+            let generation = match self.entries[slot as usize] {
+                Entry::Free { generation } => generation,
+                Entry::Occupied { .. } => unreachable!("free slots are never occupied"),
+            };
+
+            let key = K::from_index(Index { slot, generation });
+            self.entries[slot as usize] = Entry::Occupied {
+                generation,
+                value: create(key),
+            };
+            return key;
+        }
+
+        let key = K::from_index(Index {
+            slot: self.entries.len() as u32,
+            generation: 0,
+        });
+        self.entries.push(Entry::Occupied {
+            generation: 0,
+            value: create(key),
+        });
+
+        return key;
+    }
+
+    pub fn remove(&mut self, key: K) -> Option<T> {
+        let index = key.index();
+        let entry = self.entries.get_mut(index.slot as usize)?;
+
+        match entry {
+            Entry::Occupied { generation, .. } if *generation == index.generation => {}
+            _ => return None,
+        }
+
+        let freed = Entry::Free {
+            generation: index.generation.wrapping_add(1),
+        };
+
+        self.free.push(index.slot);
+        self.len -= 1;
+
+        match std::mem::replace(entry, freed) {
+            Entry::Occupied { value, .. } => Some(value),
+            Entry::Free { .. } => None,
+        }
+    }
+
+    pub fn contains(&self, key: K) -> bool {
+        return self.get(key).is_some();
+    }
+
+    pub fn get(&self, key: K) -> Option<&T> {
+        let index = key.index();
+
+        match self.entries.get(index.slot as usize)? {
+            Entry::Occupied { generation, value } if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        let index = key.index();
+
+        match self.entries.get_mut(index.slot as usize)? {
+            Entry::Occupied { generation, value } if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, entry)| match entry {
+                Entry::Occupied { generation, value } => Some((
+                    K::from_index(Index {
+                        slot: slot as u32,
+                        generation: *generation,
+                    }),
+                    value,
+                )),
+                Entry::Free { .. } => None,
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (K, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(slot, entry)| match entry {
+                Entry::Occupied { generation, value } => Some((
+                    K::from_index(Index {
+                        slot: slot as u32,
+                        generation: *generation,
+                    }),
+                    value,
+                )),
+                Entry::Free { .. } => None,
+            })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    // Removes every value `keep` returns false for
+    pub fn retain(&mut self, mut keep: impl FnMut(K, &mut T) -> bool) {
+        let removed: Vec<K> = self.iter_mut()
+            .filter_map(|(key, value)| match keep(key, value) {
+                true => None,
+                false => Some(key),
+            })
+            .collect();
+
+        for key in removed {
+            self.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ids::PlayerId;
+
+    #[test]
+    fn removed_keys_go_stale() {
+        let mut arena: Arena<PlayerId, &str> = Arena::new();
+        let key = arena.insert("a");
+
+        assert_eq!(arena.remove(key), Some("a"));
+        assert_eq!(arena.get(key), None);
+        assert!(arena.get_mut(key).is_none());
+        assert!(!arena.contains(key));
+        assert_eq!(arena.remove(key), None);
+    }
+
+    #[test]
+    fn freed_slots_are_reused_with_a_new_generation() {
+        let mut arena: Arena<PlayerId, &str> = Arena::new();
+        let a = arena.insert("a");
+        let b = arena.insert("b");
+        arena.remove(a);
+
+        let c = arena.insert("c");
+        assert_eq!(c.index().slot, a.index().slot);
+        assert_eq!(c.index().generation, a.index().generation + 1);
+
+        // The old key doesn't reach the new value
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.remove(a), None);
+        assert_eq!(arena.get(c), Some(&"c"));
+        assert_eq!(arena.get(b), Some(&"b"));
+
+        // Only once every freed slot is taken does the arena grow
+        let d = arena.insert("d");
+        assert_eq!(d.index().slot, 2);
+    }
+
+    #[test]
+    fn retain_removes_what_is_not_kept() {
+        let mut arena: Arena<PlayerId, u32> = Arena::new();
+        let keys: Vec<PlayerId> = (0..6).map(|value| arena.insert(value)).collect();
+
+        arena.retain(|_, value| {
+            *value *= 10;
+            return *value % 20 == 0;
+        });
+
+        assert_eq!(arena.values().copied().collect::<Vec<u32>>(), vec![0, 20, 40]);
+        assert_eq!(arena.keys().collect::<Vec<PlayerId>>(), vec![keys[0], keys[2], keys[4]]);
+        assert!(!arena.contains(keys[1]));
+        assert_eq!(arena.len(), 3);
+    }
+
+    #[test]
+    fn len_counts_live_values() {
+        let mut arena: Arena<PlayerId, ()> = Arena::new();
+        assert!(arena.is_empty());
+
+        let a = arena.insert(());
+        let b = arena.insert(());
+        assert_eq!(arena.len(), 2);
+
+        arena.remove(a);
+        // Removing twice or a stale key doesn't count again
+        arena.remove(a);
+        assert_eq!(arena.len(), 1);
+
+        arena.insert(());
+        arena.remove(b);
+        assert_eq!(arena.len(), 1);
+        assert!(!arena.is_empty());
+    }
+}
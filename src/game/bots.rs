@@ -0,0 +1,37 @@
+use rand::prelude::*;
+
+use crate::settings;
+use crate::geometry::Position;
+
+use super::{Game, player::JoinRequest};
+
+impl JoinRequest {
+    pub fn bot(number: usize) -> JoinRequest {
+        return JoinRequest {
+            name: format!("Bot {}", number),
+            color: None,
+            skin: None,
+            bot: true,
+        };
+    }
+}
+
+impl Game {
+    // Bots wander around at full speed, now and then turning to a random direction
+    pub fn steer_bots(&mut self) {
+        let mut rng = thread_rng();
+        let bots = self.map.players
+            .values_mut()
+            .filter(|player| player.bot);
+
+        for bot in bots {
+            let standing_still = bot.direction == Position::ZERO;
+            if !standing_still && !rng.gen_bool(settings::BOT_TURN_CHANCE) {
+                continue;
+            }
+
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            bot.direction = Position::from_angle(angle) * settings::SPEED_DISTANCE_CAP;
+        }
+    }
+}
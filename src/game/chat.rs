@@ -0,0 +1,151 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::Sender;
+
+use crate::time;
+use crate::settings;
+use crate::helper::G2UMessage;
+use crate::error::GameError;
+use crate::debug;
+
+use super::{Game, ids::PlayerId, player::PlayerColor};
+
+#[derive(Clone, Debug)]
+pub struct ChatMessage {
+    pub player_id: PlayerId,
+    pub name: String,
+    pub color: PlayerColor,
+    pub text: String,
+}
+
+// Sliding window per player: at most settings::MAX_CHAT_MESSAGES in any settings::CHAT_RATE_WINDOW_MS
+#[derive(Clone, Default)]
+pub struct ChatLimiter {
+    sent: HashMap<PlayerId, VecDeque<u128>>,
+}
+
+impl ChatLimiter {
+    pub fn allow(&mut self, player_id: PlayerId) -> bool {
+        return self.allow_at(player_id, time::now());
+    }
+
+    // Same as allow with the clock given, `now` in nanoseconds
+    fn allow_at(&mut self, player_id: PlayerId, now: u128) -> bool {
+        let window_start = now.saturating_sub(time::to_nano(settings::CHAT_RATE_WINDOW_MS));
+        let sent = self.sent.entry(player_id).or_default();
+
+        while sent.front().is_some_and(|timestamp| *timestamp < window_start) {
+            sent.pop_front();
+        }
+
+        if sent.len() >= settings::MAX_CHAT_MESSAGES {
+            return false;
+        }
+
+        sent.push_back(now);
+        return true;
+    }
+
+    pub fn forget(&mut self, player_id: PlayerId) {
+        self.sent.remove(&player_id);
+    }
+}
+
+impl Game {
+    // Checks the message and broadcasts it, the sender is told when it's dropped
+    pub fn chat(&mut self, player_id: PlayerId, text: String, tx: &Sender<G2UMessage>) -> Result<(), GameError> {
+        let text: String = text
+            .trim()
+            .chars()
+            .take(settings::MAX_CHAT_MESSAGE_LENGTH)
+            .collect();
+
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let player = match self.map.players.get(player_id) {
+            Some(player) => player,
+            None => return Ok(()),
+        };
+
+        if !self.chat_limiter.allow(player_id) {
+            debug!("chat message dropped", player_id = player_id, reason = "rate limited");
+            tx.send(G2UMessage::ChatNotice(
+                player_id,
+                String::from("You're sending messages too fast"),
+            ))?;
+            return Ok(());
+        }
+
+        debug!("chat message", player_id = player_id, name = player.name, length = text.chars().count());
+        tx.send(G2UMessage::Chat(ChatMessage {
+            player_id,
+            name: player.name.clone(),
+            color: player.color,
+            text,
+        }))?;
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::arena::Arena;
+
+    const WINDOW: u128 = time::to_nano(settings::CHAT_RATE_WINDOW_MS);
+
+    fn players(count: usize) -> Vec<PlayerId> {
+        let mut arena: Arena<PlayerId, ()> = Arena::new();
+        return (0..count).map(|_| arena.insert(())).collect();
+    }
+
+    #[test]
+    fn allows_a_few_messages_per_window() {
+        let player = players(1)[0];
+        let mut limiter = ChatLimiter::default();
+
+        for message in 0..settings::MAX_CHAT_MESSAGES {
+            assert!(limiter.allow_at(player, WINDOW + message as u128));
+        }
+        assert!(!limiter.allow_at(player, WINDOW + 100));
+        // Dropped messages don't count against the player
+        assert_eq!(limiter.sent[&player].len(), settings::MAX_CHAT_MESSAGES);
+    }
+
+    #[test]
+    fn window_slides_with_the_oldest_message() {
+        let player = players(1)[0];
+        let mut limiter = ChatLimiter::default();
+
+        // One message at the start of the window, the rest halfway through
+        let start = WINDOW;
+        assert!(limiter.allow_at(player, start));
+        for _ in 1..settings::MAX_CHAT_MESSAGES {
+            assert!(limiter.allow_at(player, start + WINDOW / 2));
+        }
+        assert!(!limiter.allow_at(player, start + WINDOW - 1));
+
+        // Only the first one has left the window, which makes room for a single message
+        assert!(limiter.allow_at(player, start + WINDOW + 1));
+        assert!(!limiter.allow_at(player, start + WINDOW + 2));
+
+        // Once the rest are out too the player can send as many again
+        let later = start + WINDOW / 2 + WINDOW + 1;
+        let allowed = (0..settings::MAX_CHAT_MESSAGES * 2).filter(|_| limiter.allow_at(player, later)).count();
+        assert_eq!(allowed, settings::MAX_CHAT_MESSAGES - 1);
+    }
+
+    #[test]
+    fn players_are_limited_separately() {
+        let [a, b] = players(2)[..] else { unreachable!() };
+        let mut limiter = ChatLimiter::default();
+
+        while limiter.allow_at(a, WINDOW) {}
+        assert!(limiter.allow_at(b, WINDOW));
+
+        limiter.forget(a);
+        assert!(limiter.allow_at(a, WINDOW));
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt;
+use std::sync::mpsc::Sender;
+
+use rand::prelude::*;
+
+use crate::settings;
+use crate::config::Config;
+use crate::helper::G2UMessage;
+use crate::error::GameError;
+use crate::{info, warn};
+use crate::geometry::Position;
+
+use super::{Game, Mode, Status, ecs::World, events::GameEvent, fruit::Fruit, ids::PlayerId, player::{JoinRequest, Player}};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Issuer {
+    // The terminal of a headless game, always allowed. Players can't send commands as it,
+    // it only comes from the channel given to Game::init_with_console
+    Console,
+    Player(PlayerId),
+}
+
+impl fmt::Display for Issuer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issuer::Console => write!(f, "console"),
+            Issuer::Player(player_id) => write!(f, "{}", player_id),
+        }
+    }
+}
+
+const HELP: &str = "Commands:
+help
+login <token>
+bots <amount>
+mass <player> <mass>
+teleport <player> <x> <y>
+kill <player>
+fruit <amount> [<x> <y>]
+tick_rate <ticks per second>
+reload
+quit
+<player> is `me`, a name or an ID";
+
+pub enum Command {
+    Help,
+    // One of the operator tokens of the config, lets the player run the other commands
+    Login(String),
+    SpawnBots(u16),
+    SetMass(String, u32),
+    Teleport(String, Position),
+    Kill(String),
+    AddFruits(u16, Option<Position>),
+    TickRate(u16),
+    ReloadConfig,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let command = match words.as_slice() {
+            ["help"] => Command::Help,
+            ["login", token] => Command::Login(token.to_string()),
+            ["bots", amount] => Command::SpawnBots(parse_number(amount)?),
+            ["mass", player, mass] => Command::SetMass(player.to_string(), parse_number(mass)?),
+            ["teleport", player, x, y] => Command::Teleport(player.to_string(), Position {
+                x: parse_number(x)?,
+                y: parse_number(y)?,
+            }),
+            ["kill", player] => Command::Kill(player.to_string()),
+            ["fruit", amount] => Command::AddFruits(parse_number(amount)?, None),
+            ["fruit", amount, x, y] => Command::AddFruits(parse_number(amount)?, Some(Position {
+                x: parse_number(x)?,
+                y: parse_number(y)?,
+            })),
+            ["tick_rate", tick_rate] => Command::TickRate(parse_number(tick_rate)?),
+            ["reload"] => Command::ReloadConfig,
+            ["quit"] => Command::Quit,
+            [] => return Err(String::from("Empty command, try `help`")),
+            _ => return Err(format!("Unknown command or wrong arguments: `{}`, try `help`", line.trim())),
+        };
+
+        return Ok(command);
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("`{}` is not a valid number", word))
+}
+
+// What gets logged of a command line, tokens are left out
+fn redact(line: &str) -> &str {
+    match line.split_whitespace().next() {
+        Some("login") => "login",
+        _ => line.trim(),
+    }
+}
+
+impl Game {
+    // Locally everyone owns the game, otherwise only players logged in with a token
+    // that's still in the config may run commands
+    fn is_allowed(&self, issuer: Issuer) -> bool {
+        let player_id = match issuer {
+            Issuer::Console => return true,
+            Issuer::Player(player_id) => player_id,
+        };
+
+        match self.mode {
+            Mode::Local => true,
+            Mode::Multiplayer => self.operators
+                .get(&player_id)
+                .is_some_and(|token| self.operator_tokens.contains(token)),
+        }
+    }
+
+    pub fn run_command(&mut self, issuer: Issuer, line: String, tx: &Sender<G2UMessage>) -> Result<(), GameError> {
+        // Anyone may try to log in
+        let output = Command::parse(&line).and_then(|command| match command {
+            Command::Login(_) => self.execute(issuer, command),
+            _ if self.is_allowed(issuer) => self.execute(issuer, command),
+            _ => Err(String::from("You aren't allowed to run commands, `login` first")),
+        });
+
+        let command = redact(&line);
+        let text = match output {
+            Ok(text) => {
+                info!("command ran", issuer = issuer, command = command);
+                text
+            }
+            Err(error) => {
+                warn!("command failed", issuer = issuer, command = command, error = error);
+                format!("Error: {}", error)
+            }
+        };
+
+        tx.send(G2UMessage::CommandOutput(issuer, text))?;
+
+        return Ok(());
+    }
+
+    // `me` is whoever issued the command, otherwise an exact name or ID
+    fn find_player(&self, issuer: Issuer, player: &str) -> Result<PlayerId, String> {
+        if player == "me" {
+            return match issuer {
+                Issuer::Player(player_id) if self.map.players.contains(player_id) => Ok(player_id),
+                _ => Err(String::from("You aren't playing")),
+            };
+        }
+
+        let matches: Vec<&Player> = self.map.players
+            .values()
+            .filter(|candidate| candidate.name == player || candidate.id.to_string() == player)
+            .collect();
+
+        match matches.as_slice() {
+            [found] => Ok(found.id),
+            [] => Err(format!("No player matches `{}`", player)),
+            _ => Err(format!("`{}` matches {} players, use the ID", player, matches.len())),
+        }
+    }
+
+    // Along with the world their body parts live in
+    fn find_player_mut(&mut self, issuer: Issuer, player: &str) -> Result<(&mut Player, &mut World), String> {
+        let player_id = self.find_player(issuer, player)?;
+
+        let player = self.map.players
+            .get_mut(player_id)
+            .ok_or_else(|| format!("No player matches `{}`", player))?;
+        return Ok((player, &mut self.map.world));
+    }
+
+    fn execute(&mut self, issuer: Issuer, command: Command) -> Result<String, String> {
+        match command {
+            Command::Help => {
+                return Ok(String::from(HELP));
+            }
+            Command::Login(token) => {
+                let player_id = match issuer {
+                    Issuer::Player(player_id) if self.mode == Mode::Multiplayer => player_id,
+                    _ => return Ok(String::from("You can already run every command")),
+                };
+
+                if !self.operator_tokens.contains(&token) {
+                    return Err(String::from("Wrong token"));
+                }
+
+                self.operators.insert(player_id, token);
+                return Ok(String::from("Logged in, you can run every command until you leave"));
+            }
+            Command::SpawnBots(amount) => {
+                for _ in 0..amount {
+                    self.add_player(JoinRequest::bot(self.map.players.len() + 1));
+                }
+
+                return Ok(format!("Spawned {} bots", amount));
+            }
+            Command::SetMass(player, mass) => {
+                let (player, world) = self.find_player_mut(issuer, &player)?;
+                let mass = mass.clamp(1, settings::MAX_MASS);
+
+                // Shared evenly between the body parts
+                let radius = Player::radius(mass / player.body_parts.len().max(1) as u32);
+                for body_part in player.body_parts.iter() {
+                    if let Some(body) = world.bodies.get_mut(*body_part) {
+                        body.radius = radius.max(1);
+                    }
+                }
+
+                return Ok(format!("{} now weighs {}", player.name, mass));
+            }
+            Command::Teleport(player, position) => {
+                let (width, height) = (self.map.width as f32, self.map.height as f32);
+                let (player, world) = self.find_player_mut(issuer, &player)?;
+
+                let position = Position {
+                    x: position.x.clamp(0.0, width),
+                    y: position.y.clamp(0.0, height),
+                };
+
+                // The other body parts keep where they are relative to the first one
+                let first = player.position(world).unwrap_or(position);
+                for body_part in player.body_parts.iter() {
+                    if let Some(center) = world.positions.get_mut(*body_part) {
+                        let moved = position + (*center - first);
+                        *center = Position::new(moved.x.clamp(0.0, width), moved.y.clamp(0.0, height));
+                    }
+                }
+
+                return Ok(format!("Teleported {} to {:.0}, {:.0}", player.name, position.x, position.y));
+            }
+            Command::Kill(player) => {
+                let player_id = self.find_player(issuer, &player)?;
+                let name = self.map.players
+                    .get(player_id)
+                    .map(|player| player.name.clone())
+                    .ok_or_else(|| format!("No player matches `{}`", player))?;
+                self.events.emit(GameEvent::PlayerDied {
+                    player_id,
+                    name: name.clone(),
+                    killer_id: None,
+                    killer_name: None,
+                });
+                self.remove_player(player_id, "killed");
+
+                return Ok(format!("Killed {}", name));
+            }
+            Command::AddFruits(amount, position) => {
+                let mut rng = thread_rng();
+                for _ in 0..amount {
+                    let mut fruit = Fruit::new(&mut rng, self.map.width, self.map.height);
+                    if let Some(position) = position {
+                        fruit.center = position;
+                    }
+                    self.map.world.spawn_fruit(fruit);
+                }
+
+                return Ok(format!("Added {} fruits, {} in total", amount, self.map.world.fruits().count()));
+            }
+            Command::TickRate(tick_rate) => {
+                self.tick_rate = tick_rate.clamp(1, settings::MAX_TICK_RATE);
+
+                return Ok(format!("Tick rate set to {}", self.tick_rate));
+            }
+            Command::ReloadConfig => {
+                let config = Config::load(settings::CONFIG_FILE_PATH);
+                self.apply_config(&config);
+
+                return Ok(format!(
+                    "Reloaded {}: tick rate {}, {} operator tokens",
+                    settings::CONFIG_FILE_PATH,
+                    self.tick_rate,
+                    self.operator_tokens.len(),
+                ));
+            }
+            Command::Quit => {
+                self.status = Status::Closed;
+
+                return Ok(String::from("Quitting"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::circle::Circle;
+
+    fn join(name: &str) -> JoinRequest {
+        return JoinRequest {
+            name: name.to_string(),
+            color: None,
+            skin: None,
+            bot: false,
+        };
+    }
+
+    fn radii(game: &Game, player_id: PlayerId) -> Vec<u32> {
+        return game.map.players
+            .get(player_id)
+            .unwrap()
+            .cells(&game.map.world)
+            .iter()
+            .map(|body_part| body_part.radius)
+            .collect();
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert!(matches!(Command::parse("  help "), Ok(Command::Help)));
+        assert!(matches!(Command::parse("login s3cret"), Ok(Command::Login(token)) if token == "s3cret"));
+        assert!(matches!(Command::parse("bots 12"), Ok(Command::SpawnBots(12))));
+        assert!(matches!(Command::parse("mass me 50000000"), Ok(Command::SetMass(player, 50_000_000)) if player == "me"));
+        assert!(matches!(Command::parse("mass me 4294967295"), Ok(Command::SetMass(_, u32::MAX))));
+        assert!(matches!(Command::parse("teleport 0.0 10 -5.5"), Ok(Command::Teleport(player, position)) if player == "0.0" && position == Position::new(10.0, -5.5)));
+        assert!(matches!(Command::parse("fruit 3"), Ok(Command::AddFruits(3, None))));
+        assert!(matches!(Command::parse("fruit 3 1 2"), Ok(Command::AddFruits(3, Some(_)))));
+        assert!(matches!(Command::parse("tick_rate 60"), Ok(Command::TickRate(60))));
+        assert!(matches!(Command::parse("quit"), Ok(Command::Quit)));
+    }
+
+    #[test]
+    fn players_log_in_with_a_token() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut game = Game::new(Mode::Multiplayer);
+        game.apply_config(&Config::parse("[console]\noperator_tokens = secret, other"));
+        let player_id = game.add_player(join("a"));
+        let me = Issuer::Player(player_id);
+        let run = |game: &mut Game, line: &str| {
+            game.run_command(me, line.to_string(), &tx).unwrap();
+            return match rx.try_recv() {
+                Ok(G2UMessage::CommandOutput(issuer, text)) if issuer == me => text,
+                _ => panic!("no answer to `{}`", line),
+            };
+        };
+
+        assert!(run(&mut game, "bots 1").starts_with("Error"));
+        assert!(run(&mut game, "login guess").starts_with("Error"));
+        assert!(!run(&mut game, "login secret").starts_with("Error"));
+        assert!(!run(&mut game, "bots 1").starts_with("Error"));
+        assert_eq!(game.map.players.len(), 2);
+
+        // Revoking the token drops whoever used it
+        game.apply_config(&Config::parse("[console]\noperator_tokens = other"));
+        assert!(run(&mut game, "bots 1").starts_with("Error"));
+
+        game.execute(me, Command::Login(String::from("other"))).unwrap();
+        game.remove_player(player_id, "left");
+        assert!(game.operators.is_empty());
+    }
+
+    #[test]
+    fn only_the_console_and_local_players_skip_the_login() {
+        let mut game = Game::new(Mode::Multiplayer);
+        let player_id = game.add_player(join("a"));
+
+        // Without tokens nobody can log in
+        assert!(game.execute(Issuer::Player(player_id), Command::Login(String::new())).is_err());
+        assert!(!game.is_allowed(Issuer::Player(player_id)));
+        assert!(game.is_allowed(Issuer::Console));
+
+        game.mode = Mode::Local;
+        assert!(game.is_allowed(Issuer::Player(player_id)));
+    }
+
+    #[test]
+    fn tokens_are_not_logged() {
+        assert_eq!(redact(" login secret "), "login");
+        assert_eq!(redact(" bots 3 "), "bots 3");
+    }
+
+    #[test]
+    fn parse_rejects_bad_arguments() {
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("dance").is_err());
+        assert!(Command::parse("login").is_err());
+        assert!(Command::parse("bots").is_err());
+        assert!(Command::parse("bots -1").is_err());
+        assert!(Command::parse("bots 70000").is_err());
+        assert!(Command::parse("mass me 4294967296").is_err());
+        assert!(Command::parse("mass me heavy").is_err());
+        assert!(Command::parse("fruit 3 1").is_err());
+    }
+
+    #[test]
+    fn mass_is_kept_within_bounds() {
+        let mut game = Game::new(Mode::Local);
+        let player_id = game.add_player(join("a"));
+        let me = Issuer::Player(player_id);
+
+        for mass in [50_000_000, u32::MAX] {
+            assert!(game.execute(me, Command::SetMass(String::from("me"), mass)).is_ok());
+            assert_eq!(radii(&game, player_id), vec![Player::radius(settings::MAX_MASS)]);
+        }
+
+        game.execute(me, Command::SetMass(String::from("a"), 0)).unwrap();
+        assert_eq!(radii(&game, player_id), vec![10]);
+
+        game.execute(me, Command::SetMass(String::from("a"), 400)).unwrap();
+        assert_eq!(radii(&game, player_id), vec![200]);
+    }
+
+    #[test]
+    fn mass_is_shared_between_the_body_parts() {
+        let mut game = Game::new(Mode::Local);
+        let player_id = game.add_player(join("a"));
+        let me = Issuer::Player(player_id);
+
+        game.execute(me, Command::SetMass(String::from("me"), 400)).unwrap();
+        let player = game.map.players.get_mut(player_id).unwrap();
+        player.split(&mut game.map.world);
+        assert_eq!(player.body_parts.len(), 2);
+
+        game.execute(me, Command::SetMass(String::from("me"), settings::MAX_MASS)).unwrap();
+        let expected = Player::radius(settings::MAX_MASS / 2);
+        assert_eq!(radii(&game, player_id), vec![expected, expected]);
+    }
+
+    #[test]
+    fn huge_body_parts_eat_without_overflowing() {
+        let mut game = Game::new(Mode::Local);
+        let (big, small) = (game.add_player(join("big")), game.add_player(join("small")));
+
+        game.execute(Issuer::Console, Command::SetMass(String::from("big"), settings::MAX_MASS)).unwrap();
+        game.execute(Issuer::Console, Command::SetMass(String::from("small"), settings::MAX_MASS / 2)).unwrap();
+        game.execute(Issuer::Console, Command::Teleport(String::from("big"), Position::new(3000.0, 3000.0))).unwrap();
+        game.execute(Issuer::Console, Command::Teleport(String::from("small"), Position::new(3000.0, 3000.0))).unwrap();
+
+        let meals = crate::game::ecs::systems::collision::run(&mut game.map.world, &game.map.players, 1);
+        let eaten = meals.iter().find_map(|meal| match meal {
+            GameEvent::CellEaten { eaten_id, mass, .. } => Some((*eaten_id, *mass)),
+            _ => None,
+        });
+
+        assert_eq!(eaten, Some((small, Player::mass(&Circle { center: Position::ZERO, radius: Player::radius(settings::MAX_MASS / 2) }))));
+        assert!(radii(&game, big)[0] > Player::radius(settings::MAX_MASS));
+    }
+
+    #[test]
+    fn commands_need_a_player_to_act_on() {
+        let mut game = Game::new(Mode::Local);
+
+        assert!(game.execute(Issuer::Console, Command::SetMass(String::from("me"), 10)).is_err());
+        assert!(game.execute(Issuer::Console, Command::Kill(String::from("nobody"))).is_err());
+    }
+}
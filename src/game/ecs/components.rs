@@ -0,0 +1,27 @@
+use crate::geometry::Position;
+use crate::game::ids::PlayerId;
+
+// Positions are plain geometry::Position, the center of the entity on the map
+
+// Round shape of the entity around its position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Body {
+    pub radius: u32,
+}
+
+// How far the entity moves every tick, set by whatever steers it
+#[derive(Clone, Copy, Debug)]
+pub struct Velocity(pub Position);
+
+// The player a body part belongs to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Owner(pub PlayerId);
+
+// What may eat the entity, see ecs::systems::collision
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edible {
+    // Any body part covering its center
+    Fruit,
+    // A body part of another player, settings::EAT_RADIUS_RATIO times bigger and covering its center
+    Cell,
+}
@@ -0,0 +1,242 @@
+use crate::settings;
+use crate::geometry::{Position, circle::Circle};
+
+use super::arena::Arena;
+use super::ids::{Entity, PlayerId};
+use super::spatial::SpatialGrid;
+
+// Mods
+pub mod storage;
+pub mod components;
+pub mod parallel;
+pub mod systems;
+
+use storage::Components;
+use components::{Body, Edible, Owner, Velocity};
+
+// Everything on the map that isn't a player: body parts, fruits, and whatever comes next.
+// An entity is only an ID, what it is and does comes from the components it has,
+// and the systems in ecs::systems each run over the entities with the components they care about.
+#[derive(Debug)]
+pub struct World {
+    pub width: u32,
+    pub height: u32,
+    entities: Arena<Entity, ()>,
+    pub positions: Components<Position>,
+    pub bodies: Components<Body>,
+    pub velocities: Components<Velocity>,
+    pub owners: Components<Owner>,
+    pub edibles: Components<Edible>,
+    // Edible entities by position, rebuilt by the collision system every tick
+    pub grid: SpatialGrid<Entity>,
+}
+
+// Snapshots are cloned every tick and don't need the collision system's grid
+impl Clone for World {
+    fn clone(&self) -> World {
+        return World {
+            width: self.width,
+            height: self.height,
+            entities: self.entities.clone(),
+            positions: self.positions.clone(),
+            bodies: self.bodies.clone(),
+            velocities: self.velocities.clone(),
+            owners: self.owners.clone(),
+            edibles: self.edibles.clone(),
+            grid: self.grid.without_cells(),
+        };
+    }
+}
+
+impl World {
+    pub fn new(width: u32, height: u32) -> World {
+        return World {
+            width,
+            height,
+            entities: Arena::new(),
+            positions: Components::new(),
+            bodies: Components::new(),
+            velocities: Components::new(),
+            owners: Components::new(),
+            edibles: Components::new(),
+            grid: SpatialGrid::new(width, height, settings::SPATIAL_CELL_SIZE),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entities.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entities.is_empty();
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        return self.entities.contains(entity);
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.keys()
+    }
+
+    // A new entity at `position`, components are added on top of it
+    pub fn spawn(&mut self, position: Position) -> Entity {
+        let entity = self.entities.insert(());
+        self.positions.insert(entity, position);
+
+        return entity;
+    }
+
+    // Removes the entity along with every component it had
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if self.entities.remove(entity).is_none() {
+            return false;
+        }
+
+        self.positions.remove(entity);
+        self.bodies.remove(entity);
+        self.velocities.remove(entity);
+        self.owners.remove(entity);
+        self.edibles.remove(entity);
+
+        return true;
+    }
+
+    pub fn spawn_fruit(&mut self, fruit: Circle) -> Entity {
+        let entity = self.spawn(fruit.center);
+        self.bodies.insert(entity, Body { radius: fruit.radius });
+        self.edibles.insert(entity, Edible::Fruit);
+
+        return entity;
+    }
+
+    // A body part of `owner`, standing still until the movement system steers it
+    pub fn spawn_cell(&mut self, owner: PlayerId, circle: Circle) -> Entity {
+        let entity = self.spawn(circle.center);
+        self.bodies.insert(entity, Body { radius: circle.radius });
+        self.velocities.insert(entity, Velocity(Position::ZERO));
+        self.owners.insert(entity, Owner(owner));
+        self.edibles.insert(entity, Edible::Cell);
+
+        return entity;
+    }
+
+    // Position and body of the entity, None if it's gone or has no body
+    pub fn circle(&self, entity: Entity) -> Option<Circle> {
+        return Some(Circle {
+            center: *self.positions.get(entity)?,
+            radius: self.bodies.get(entity)?.radius,
+        });
+    }
+
+    pub fn set_circle(&mut self, entity: Entity, circle: Circle) {
+        if let Some(position) = self.positions.get_mut(entity) {
+            *position = circle.center;
+        }
+        if let Some(body) = self.bodies.get_mut(entity) {
+            body.radius = circle.radius;
+        }
+    }
+
+    pub fn is_fruit(&self, entity: Entity) -> bool {
+        return self.edibles.get(entity) == Some(&Edible::Fruit);
+    }
+
+    pub fn fruits(&self) -> impl Iterator<Item = (Entity, Circle)> + '_ {
+        self.edibles
+            .iter()
+            .filter(|(_, edible)| **edible == Edible::Fruit)
+            .filter_map(|(entity, _)| Some((entity, self.circle(entity)?)))
+    }
+
+    // Every body part with the player it belongs to
+    pub fn cells(&self) -> impl Iterator<Item = (Entity, PlayerId, Circle)> + '_ {
+        self.owners
+            .iter()
+            .filter_map(|(entity, owner)| Some((entity, owner.0, self.circle(entity)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::arena::Arena;
+    use crate::game::player::{JoinRequest, Player};
+
+    pub fn circle(x: f32, y: f32, radius: u32) -> Circle {
+        return Circle {
+            center: Position { x, y },
+            radius,
+        };
+    }
+
+    pub fn player(players: &mut Arena<PlayerId, Player>, name: &str) -> PlayerId {
+        return players.insert_with(|player_id| Player::new(player_id, JoinRequest {
+            name: name.to_string(),
+            color: None,
+            skin: None,
+            bot: false,
+        }));
+    }
+
+    // `cells` body parts of as many players, and `fruits` fruits, scattered on a small map so they run into each other
+    pub fn crowded_world(seed: u64, cells: usize, fruits: usize) -> (World, Arena<PlayerId, Player>) {
+        use rand::prelude::*;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut players = Arena::new();
+        let mut world = World::new(2000, 2000);
+
+        for cell in 0..cells {
+            let owner = player(&mut players, &cell.to_string());
+            let body_part = world.spawn_cell(owner, circle(
+                rng.gen_range(0.0..2000.0),
+                rng.gen_range(0.0..2000.0),
+                rng.gen_range(5..40),
+            ));
+            world.velocities.insert(body_part, components::Velocity(Position {
+                x: rng.gen_range(-3.0..3.0),
+                y: rng.gen_range(-3.0..3.0),
+            }));
+            players.get_mut(owner).unwrap().body_parts.push(body_part);
+        }
+        for _ in 0..fruits {
+            world.spawn_fruit(circle(rng.gen_range(0.0..2000.0), rng.gen_range(0.0..2000.0), 10));
+        }
+
+        return (world, players);
+    }
+
+    // Everything that can be told apart, in entity order
+    pub fn snapshot(world: &World) -> Vec<(Entity, f32, f32, u32)> {
+        return world
+            .entities()
+            .filter_map(|entity| world.circle(entity).map(|circle| (entity, circle.center.x, circle.center.y, circle.radius)))
+            .collect();
+    }
+
+    #[test]
+    fn despawn_removes_every_component() {
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = World::new(100, 100);
+
+        let cell = world.spawn_cell(owner, circle(10.0, 10.0, 5));
+        let fruit = world.spawn_fruit(circle(20.0, 20.0, 1));
+        assert_eq!(world.cells().count(), 1);
+        assert_eq!(world.fruits().count(), 1);
+
+        assert!(world.despawn(cell));
+        assert!(!world.despawn(cell));
+        assert!(world.circle(cell).is_none());
+        assert!(world.velocities.is_empty() && world.owners.is_empty());
+        assert_eq!(world.edibles.len(), 1);
+        assert!(world.is_fruit(fruit));
+
+        // The freed slot doesn't bring the old components back
+        let reused = world.spawn(Position { x: 1.0, y: 1.0 });
+        assert!(world.circle(reused).is_none());
+        assert!(!world.is_fruit(reused));
+        assert_eq!(world.len(), 2);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::settings;
+
+// Every core, what `game.threads = 0` asks for
+pub fn default_threads() -> usize {
+    return std::thread::available_parallelism().map_or(1, |threads| threads.get());
+}
+
+// Maps every item on up to `threads` scoped threads, each taking a contiguous chunk.
+// The results come back in the order of `items` whatever the number of threads, so the
+// systems using it stay deterministic. Small inputs aren't worth a thread, below
+// settings::MIN_ITEMS_PER_THREAD items per thread the work is done on fewer threads, down to
+// the calling one alone.
+pub fn map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads.min(items.len() / settings::MIN_ITEMS_PER_THREAD).max(1);
+    if threads == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+
+    return std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(results) => results,
+                // Same as if it had panicked on the calling thread
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_order_of_the_items() {
+        let items: Vec<usize> = (0..settings::MIN_ITEMS_PER_THREAD * 5 + 3).collect();
+        let sequential = map(&items, 1, |item| item * 2);
+
+        for threads in [2, 3, 8, 64] {
+            assert_eq!(map(&items, threads, |item| item * 2), sequential);
+        }
+        assert_eq!(sequential[7], 14);
+    }
+
+    #[test]
+    fn small_inputs_stay_on_the_calling_thread() {
+        let caller = std::thread::current().id();
+        let items = vec![0; settings::MIN_ITEMS_PER_THREAD - 1];
+
+        let threads = map(&items, 8, |_| std::thread::current().id());
+        assert!(threads.iter().all(|thread| *thread == caller));
+        assert!(map(&Vec::<u8>::new(), 8, |item| *item).is_empty());
+    }
+}
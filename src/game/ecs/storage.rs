@@ -0,0 +1,151 @@
+use crate::game::arena::{Index, Key};
+use crate::game::ids::Entity;
+
+// Every value of one component type, indexed by the slot of the entity it belongs to.
+// Entries remember the generation they were added for, so a stale entity finds nothing.
+#[derive(Clone, Debug)]
+pub struct Components<T> {
+    entries: Vec<Option<(u32, T)>>,
+    len: usize,
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Components<T> {
+        return Components::new();
+    }
+}
+
+impl<T> Components<T> {
+    pub fn new() -> Components<T> {
+        return Components {
+            entries: vec![],
+            len: 0,
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    // Replaces whatever the entity had before
+    pub fn insert(&mut self, entity: Entity, value: T) {
+        let index = entity.index();
+        let slot = index.slot as usize;
+
+        if slot >= self.entries.len() {
+            self.entries.resize_with(slot + 1, || None);
+        }
+
+        if self.entries[slot].replace((index.generation, value)).is_none() {
+            self.len += 1;
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let index = entity.index();
+        let entry = self.entries.get_mut(index.slot as usize)?;
+
+        match entry {
+            Some((generation, _)) if *generation == index.generation => {}
+            _ => return None,
+        }
+
+        self.len -= 1;
+        return entry.take().map(|(_, value)| value);
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        return self.get(entity).is_some();
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        let index = entity.index();
+
+        match self.entries.get(index.slot as usize)? {
+            Some((generation, value)) if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        let index = entity.index();
+
+        match self.entries.get_mut(index.slot as usize)? {
+            Some((generation, value)) if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    // In slot order, like the arena handing out the entities
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, entry)| {
+                let (generation, value) = entry.as_ref()?;
+                Some((entity_at(slot, *generation), value))
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(slot, entry)| {
+                let (generation, value) = entry.as_mut()?;
+                Some((entity_at(slot, *generation), value))
+            })
+    }
+}
+
+fn entity_at(slot: usize, generation: u32) -> Entity {
+    return Entity::from_index(Index {
+        slot: slot as u32,
+        generation,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::arena::Arena;
+
+    #[test]
+    fn stale_entities_find_nothing() {
+        let mut entities: Arena<Entity, ()> = Arena::new();
+        let mut components = Components::new();
+
+        let first = entities.insert(());
+        components.insert(first, 1);
+        entities.remove(first);
+        components.remove(first);
+
+        // Reuses the slot of `first` with a new generation
+        let second = entities.insert(());
+        components.insert(second, 2);
+
+        assert_eq!(components.get(first), None);
+        assert_eq!(components.get(second), Some(&2));
+        assert_eq!(components.remove(first), None);
+        assert_eq!(components.len(), 1);
+    }
+
+    #[test]
+    fn insert_replaces_and_iterates_in_slot_order() {
+        let mut entities: Arena<Entity, ()> = Arena::new();
+        let mut components = Components::new();
+
+        let keys: Vec<Entity> = (0..3).map(|_| entities.insert(())).collect();
+        components.insert(keys[2], "c");
+        components.insert(keys[0], "a");
+        components.insert(keys[0], "b");
+
+        let values: Vec<&str> = components.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, vec!["b", "c"]);
+        assert_eq!(components.len(), 2);
+    }
+}
@@ -0,0 +1,276 @@
+use std::cmp::Reverse;
+
+use crate::settings;
+use crate::geometry::{circle::Circle, shape::Shape};
+use crate::game::{arena::Arena, events::GameEvent, ids::{Entity, PlayerId}, player::Player};
+
+use super::super::{World, components::Edible, parallel};
+
+// What a body part can eat this pass, found from the state at the start of it
+struct Plan {
+    eater: Entity,
+    owner: PlayerId,
+    radius: u32,
+    prey: Vec<Entity>,
+}
+
+// Body parts eat the fruits, then each other. Returns what got eaten, in order,
+// as FruitEaten and CellEaten events. Players left without body parts are up to the caller.
+//
+// Each pass first plans on up to `threads` threads what every body part can reach, then eats
+// on the calling thread, biggest body part first. The plans only read the world as it was before
+// the pass, so the outcome is the same whatever the number of threads.
+pub fn run(world: &mut World, players: &Arena<PlayerId, Player>, threads: usize) -> Vec<GameEvent> {
+    index(world);
+
+    let mut meals = vec![];
+    let plans = plan(world, players, threads, Edible::Fruit);
+    eat_fruits(world, plans, &mut meals);
+    let plans = plan(world, players, threads, Edible::Cell);
+    eat_cells(world, plans, &mut meals);
+
+    return meals;
+}
+
+fn index(world: &mut World) {
+    world.grid.clear();
+    for (entity, _) in world.edibles.iter() {
+        if let Some(position) = world.positions.get(entity) {
+            world.grid.insert(entity, *position);
+        }
+    }
+}
+
+fn is_active(players: &Arena<PlayerId, Player>, player_id: PlayerId) -> bool {
+    return players.get(player_id).is_some_and(|player| !player.paused);
+}
+
+// Fruits are eaten by any body part covering their center. Body parts of other players are
+// eaten by one covering their center that's settings::EAT_RADIUS_RATIO times bigger.
+fn can_eat(world: &World, players: &Arena<PlayerId, Player>, eater: (PlayerId, Circle), prey: Entity, kind: Edible) -> bool {
+    let (owner, reach) = eater;
+    let prey_circle = match (world.edibles.get(prey), world.circle(prey)) {
+        (Some(edible), Some(prey_circle)) if *edible == kind => prey_circle,
+        _ => return false,
+    };
+
+    if !reach.contains_position(prey_circle.center) {
+        return false;
+    }
+
+    match kind {
+        Edible::Fruit => true,
+        Edible::Cell => {
+            let prey_id = match world.owners.get(prey) {
+                Some(prey_owner) => prey_owner.0,
+                None => return false,
+            };
+
+            prey_id != owner
+                && is_active(players, prey_id)
+                && reach.radius as f32 >= prey_circle.radius as f32 * settings::EAT_RADIUS_RATIO
+        }
+    }
+}
+
+// The body parts of the players who aren't paused are split across the threads by spatial cell,
+// so each thread looks at one area of the map. The plans come back biggest eater first,
+// ties in entity order.
+fn plan(world: &World, players: &Arena<PlayerId, Player>, threads: usize, kind: Edible) -> Vec<Plan> {
+    let mut eaters: Vec<(Entity, PlayerId, Circle)> = world
+        .cells()
+        .filter(|(_, owner, _)| is_active(players, *owner))
+        .collect();
+    eaters.sort_by_key(|(eater, _, reach)| (world.grid.cell_index(reach.center), *eater));
+
+    let mut plans = parallel::map(&eaters, threads, |(eater, owner, reach)| Plan {
+        eater: *eater,
+        owner: *owner,
+        radius: reach.radius,
+        prey: world.grid
+            .query(*reach)
+            .into_iter()
+            .filter(|prey| can_eat(world, players, (*owner, *reach), *prey, kind))
+            .collect(),
+    });
+    plans.sort_by_key(|plan| (Reverse(plan.radius), plan.eater));
+
+    return plans;
+}
+
+// Every fruit grows its eater a bit
+fn eat_fruits(world: &mut World, plans: Vec<Plan>, meals: &mut Vec<GameEvent>) {
+    for plan in plans {
+        for fruit_id in plan.prey {
+            // Already eaten by a bigger body part
+            let fruit = match world.circle(fruit_id) {
+                Some(fruit) => fruit,
+                None => continue,
+            };
+
+            if let Some(body) = world.bodies.get_mut(plan.eater) {
+                body.radius += fruit.radius / 10;
+            }
+            world.despawn(fruit_id);
+            meals.push(GameEvent::FruitEaten {
+                player_id: plan.owner,
+                fruit_id,
+                position: fruit.center,
+            });
+        }
+    }
+}
+
+// Biggest first so a body part eaten this pass can't eat anything itself.
+// The eater takes the whole area of its prey.
+fn eat_cells(world: &mut World, plans: Vec<Plan>, meals: &mut Vec<GameEvent>) {
+    for plan in plans {
+        for prey in plan.prey {
+            // Eaten by a bigger one already, it can't eat anymore
+            let eater_circle = match world.circle(plan.eater) {
+                Some(eater_circle) => eater_circle,
+                None => break,
+            };
+            let (prey_id, prey_circle) = match (world.owners.get(prey), world.circle(prey)) {
+                (Some(prey_owner), Some(prey_circle)) => (prey_owner.0, prey_circle),
+                _ => continue,
+            };
+
+            let radius = (eater_circle.radius as f64).hypot(prey_circle.radius as f64) as u32;
+            world.set_circle(plan.eater, Circle { radius, ..eater_circle });
+            world.despawn(prey);
+
+            meals.push(GameEvent::CellEaten {
+                eater_id: plan.owner,
+                eater_cell_id: plan.eater,
+                eaten_id: prey_id,
+                eaten_cell_id: prey,
+                position: prey_circle.center,
+                mass: Player::mass(&prey_circle),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ecs::tests::{circle, crowded_world, player, snapshot};
+
+    fn world() -> World {
+        return World::new(1000, 1000);
+    }
+
+    #[test]
+    fn body_parts_eat_the_fruits_they_cover() {
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = world();
+
+        let body_part = world.spawn_cell(owner, circle(100.0, 100.0, 20));
+        let covered = world.spawn_fruit(circle(110.0, 100.0, 10));
+        let touching = world.spawn_fruit(circle(125.0, 100.0, 10));
+
+        let meals = run(&mut world, &players, 1);
+
+        assert_eq!(meals.len(), 1);
+        assert!(matches!(meals[0], GameEvent::FruitEaten { fruit_id, player_id, .. } if fruit_id == covered && player_id == owner));
+        assert!(!world.contains(covered));
+        assert!(world.contains(touching));
+        assert_eq!(world.circle(body_part).unwrap().radius, 21);
+    }
+
+    #[test]
+    fn bigger_body_parts_eat_smaller_ones() {
+        let mut players = Arena::new();
+        let (eater_id, prey_id) = (player(&mut players, "eater"), player(&mut players, "prey"));
+        let mut world = world();
+
+        let eater = world.spawn_cell(eater_id, circle(100.0, 100.0, 40));
+        let prey = world.spawn_cell(prey_id, circle(120.0, 100.0, 30));
+
+        let meals = run(&mut world, &players, 1);
+
+        assert_eq!(meals.len(), 1);
+        match meals[0] {
+            GameEvent::CellEaten { eater_id: meal_eater, eaten_id, eater_cell_id, eaten_cell_id, mass, .. } => {
+                assert_eq!((meal_eater, eaten_id), (eater_id, prey_id));
+                assert_eq!((eater_cell_id, eaten_cell_id), (eater, prey));
+                assert_eq!(mass, 9);
+            }
+            _ => panic!("expected a cell to be eaten"),
+        }
+        assert!(!world.contains(prey));
+        // 40² + 30² = 50²
+        assert_eq!(world.circle(eater).unwrap().radius, 50);
+    }
+
+    #[test]
+    fn nothing_eaten_without_the_size_ratio_or_coverage() {
+        let mut players = Arena::new();
+        let (a, b, c) = (player(&mut players, "a"), player(&mut players, "b"), player(&mut players, "c"));
+        let mut world = world();
+
+        // Not settings::EAT_RADIUS_RATIO times bigger
+        world.spawn_cell(a, circle(100.0, 100.0, 40));
+        world.spawn_cell(b, circle(110.0, 100.0, 38));
+        // Big enough but its center is out of reach
+        world.spawn_cell(c, circle(500.0, 500.0, 100));
+        world.spawn_cell(a, circle(605.0, 500.0, 10));
+
+        assert!(run(&mut world, &players, 1).is_empty());
+        assert_eq!(world.cells().count(), 4);
+    }
+
+    #[test]
+    fn own_and_paused_body_parts_are_safe() {
+        let mut players = Arena::new();
+        let (a, b) = (player(&mut players, "a"), player(&mut players, "b"));
+        let mut world = world();
+
+        world.spawn_cell(a, circle(100.0, 100.0, 50));
+        world.spawn_cell(a, circle(110.0, 100.0, 10));
+        world.spawn_cell(b, circle(90.0, 100.0, 10));
+        players.get_mut(b).unwrap().paused = true;
+
+        assert!(run(&mut world, &players, 1).is_empty());
+
+        players.get_mut(b).unwrap().paused = false;
+        assert_eq!(run(&mut world, &players, 1).len(), 1);
+        assert_eq!(world.cells().count(), 2);
+    }
+
+    #[test]
+    fn eaten_body_parts_dont_eat() {
+        let mut players = Arena::new();
+        let (a, b, c) = (player(&mut players, "a"), player(&mut players, "b"), player(&mut players, "c"));
+        let mut world = world();
+
+        let biggest = world.spawn_cell(a, circle(100.0, 100.0, 80));
+        let middle = world.spawn_cell(b, circle(120.0, 100.0, 40));
+        world.spawn_cell(c, circle(130.0, 100.0, 20));
+
+        let meals = run(&mut world, &players, 1);
+
+        assert_eq!(meals.len(), 2);
+        assert!(meals.iter().all(|meal| matches!(meal, GameEvent::CellEaten { eater_cell_id, .. } if *eater_cell_id == biggest)));
+        assert!(!world.contains(middle));
+    }
+
+    #[test]
+    fn threads_dont_change_the_outcome() {
+        let (world, players) = crowded_world(7, 6000, 6000);
+
+        let mut sequential = world.clone();
+        let sequential_meals = run(&mut sequential, &players, 1);
+        assert!(!sequential_meals.is_empty());
+
+        for threads in [2, 5] {
+            let mut parallel = world.clone();
+            let parallel_meals = run(&mut parallel, &players, threads);
+
+            assert_eq!(format!("{:?}", parallel_meals), format!("{:?}", sequential_meals));
+            assert_eq!(snapshot(&parallel), snapshot(&sequential));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::settings;
+use crate::game::{arena::Arena, ids::PlayerId, player::Player};
+
+use super::super::World;
+
+// Big body parts slowly shrink, every settings::DECAY_INTERVAL_TICKS ticks they lose a unit of radius
+// until they're down to settings::DECAY_MIN_RADIUS. Paused players are left alone.
+pub fn run(world: &mut World, players: &Arena<PlayerId, Player>, tick: u64) {
+    if !tick.is_multiple_of(settings::DECAY_INTERVAL_TICKS) {
+        return;
+    }
+
+    for (entity, owner) in world.owners.iter() {
+        let paused = players.get(owner.0).is_none_or(|player| player.paused);
+        if paused {
+            continue;
+        }
+
+        if let Some(body) = world.bodies.get_mut(entity) {
+            if body.radius > settings::DECAY_MIN_RADIUS {
+                body.radius -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ecs::tests::{circle, player};
+
+    #[test]
+    fn only_big_body_parts_decay() {
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = World::new(1000, 1000);
+
+        let big = world.spawn_cell(owner, circle(100.0, 100.0, settings::DECAY_MIN_RADIUS + 2));
+        let small = world.spawn_cell(owner, circle(300.0, 100.0, settings::DECAY_MIN_RADIUS));
+        let fruit = world.spawn_fruit(circle(500.0, 100.0, settings::DECAY_MIN_RADIUS + 2));
+
+        // Between two intervals nothing happens
+        run(&mut world, &players, 1);
+        assert_eq!(world.circle(big).unwrap().radius, settings::DECAY_MIN_RADIUS + 2);
+
+        for interval in 0..4 {
+            run(&mut world, &players, interval * settings::DECAY_INTERVAL_TICKS);
+        }
+        assert_eq!(world.circle(big).unwrap().radius, settings::DECAY_MIN_RADIUS);
+        assert_eq!(world.circle(small).unwrap().radius, settings::DECAY_MIN_RADIUS);
+        assert_eq!(world.circle(fruit).unwrap().radius, settings::DECAY_MIN_RADIUS + 2);
+    }
+
+    #[test]
+    fn paused_players_dont_decay() {
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = World::new(1000, 1000);
+
+        let body_part = world.spawn_cell(owner, circle(100.0, 100.0, settings::DECAY_MIN_RADIUS + 1));
+        players.get_mut(owner).unwrap().paused = true;
+
+        run(&mut world, &players, 0);
+        assert_eq!(world.circle(body_part).unwrap().radius, settings::DECAY_MIN_RADIUS + 1);
+    }
+}
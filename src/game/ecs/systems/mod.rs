@@ -0,0 +1,6 @@
+// Each system is a plain function over the world, Game::update runs them in order every tick:
+// movement, then decay, collision and spawning
+pub mod movement;
+pub mod decay;
+pub mod collision;
+pub mod spawning;
@@ -0,0 +1,116 @@
+use crate::geometry::{Position, shape::Shape, rectangle::{Rectangle, RectangleSize, Size}};
+use crate::game::{Game, arena::Arena, ids::{Entity, PlayerId}, player::Player};
+
+use super::super::{World, components::Velocity, parallel};
+
+// Gives every body part the velocity its player steers it at, paused players stand still
+pub fn steer(world: &mut World, players: &Arena<PlayerId, Player>, tick_rate: u16) {
+    for player in players.values() {
+        let step = match player.paused {
+            true => Position::ZERO,
+            false => Game::player_step(tick_rate, player, world),
+        };
+
+        for body_part in player.body_parts.iter() {
+            if let Some(velocity) = world.velocities.get_mut(*body_part) {
+                *velocity = Velocity(step);
+            }
+        }
+    }
+}
+
+// Moves everything with a velocity, unless that would take it off the map.
+// Where each entity ends up is worked out on up to `threads` threads, then written back in order.
+pub fn run(world: &mut World, threads: usize) {
+    let map_rect = Rectangle {
+        position: Position {
+            x: world.width as f32 / 2.0,
+            y: world.height as f32 / 2.0,
+        },
+        size: Size::Rectangle(RectangleSize {
+            width: world.width,
+            height: world.height,
+        }),
+    };
+
+    let moving: Vec<(Entity, Velocity)> = world.velocities
+        .iter()
+        .map(|(entity, velocity)| (entity, *velocity))
+        .collect();
+
+    let positions = &world.positions;
+    let moves = parallel::map(&moving, threads, |(entity, velocity)| {
+        let future_position = *positions.get(*entity)? + velocity.0;
+
+        map_rect.contains_position(future_position).then_some((*entity, future_position))
+    });
+
+    for (entity, future_position) in moves.into_iter().flatten() {
+        if let Some(position) = world.positions.get_mut(entity) {
+            *position = future_position;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ecs::tests::{circle, crowded_world, player, snapshot};
+
+    #[test]
+    fn moves_by_velocity_within_the_map() {
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = World::new(100, 100);
+
+        let inside = world.spawn_cell(owner, circle(50.0, 50.0, 5));
+        let edge = world.spawn_cell(owner, circle(98.0, 50.0, 5));
+        let fruit = world.spawn_fruit(circle(10.0, 10.0, 1));
+        for entity in [inside, edge] {
+            world.velocities.insert(entity, Velocity(Position { x: 5.0, y: -2.0 }));
+        }
+
+        run(&mut world, 1);
+
+        let moved = world.circle(inside).unwrap().center;
+        assert_eq!((moved.x, moved.y), (55.0, 48.0));
+        // Would have left the map
+        assert_eq!(world.circle(edge).unwrap().center.x, 98.0);
+        // Fruits have no velocity
+        assert_eq!(world.circle(fruit).unwrap().center.x, 10.0);
+    }
+
+    #[test]
+    fn paused_players_stand_still() {
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = World::new(1000, 1000);
+
+        let body_part = world.spawn_cell(owner, circle(500.0, 500.0, 20));
+        let player = players.get_mut(owner).unwrap();
+        player.body_parts.push(body_part);
+        player.direction = Position { x: 100.0, y: 0.0 };
+
+        steer(&mut world, &players, 60);
+        assert!(world.velocities.get(body_part).unwrap().0.x > 0.0);
+
+        players.get_mut(owner).unwrap().paused = true;
+        steer(&mut world, &players, 60);
+        run(&mut world, 1);
+        assert_eq!(world.circle(body_part).unwrap().center.x, 500.0);
+    }
+
+    #[test]
+    fn threads_dont_change_the_outcome() {
+        let (world, _) = crowded_world(3, 5000, 100);
+
+        let mut sequential = world.clone();
+        run(&mut sequential, 1);
+
+        let mut parallel = world.clone();
+        run(&mut parallel, 4);
+
+        assert_eq!(snapshot(&parallel), snapshot(&sequential));
+        assert_ne!(snapshot(&sequential), snapshot(&world));
+    }
+}
@@ -0,0 +1,76 @@
+use rand::prelude::*;
+
+use crate::settings;
+use crate::geometry::{Position, circle::Circle};
+use crate::game::{fruit::Fruit, ids::{Entity, PlayerId}};
+
+use super::super::World;
+
+// Tops the fruits up to settings::FRUITS. Ejected fruits can push the total above it,
+// those aren't replaced once eaten.
+pub fn fruits(world: &mut World, rng: &mut impl Rng) -> usize {
+    let missing = (settings::FRUITS as usize).saturating_sub(world.fruits().count());
+
+    for _ in 0..missing {
+        world.spawn_fruit(Fruit::new(rng, world.width, world.height));
+    }
+
+    return missing;
+}
+
+// First body part of a player who just joined, somewhere random on the map
+pub fn body_part(world: &mut World, owner: PlayerId, rng: &mut impl Rng) -> Entity {
+    let spawn = Circle {
+        center: Position {
+            x: rng.gen_range(0..world.width) as f32,
+            y: rng.gen_range(0..world.height) as f32,
+        },
+        radius: settings::INITIAL_PLAYER_SCORE,
+    };
+
+    return world.spawn_cell(owner, spawn);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use crate::game::arena::Arena;
+    use crate::game::ecs::tests::{circle, player};
+
+    #[test]
+    fn refills_fruits_up_to_the_limit() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut world = World::new(1000, 1000);
+
+        assert_eq!(fruits(&mut world, &mut rng), settings::FRUITS as usize);
+        assert_eq!(fruits(&mut world, &mut rng), 0);
+
+        let eaten: Vec<Entity> = world.fruits().take(3).map(|(fruit, _)| fruit).collect();
+        for fruit in eaten {
+            world.despawn(fruit);
+        }
+        assert_eq!(fruits(&mut world, &mut rng), 3);
+
+        // Ejected fruits above the limit stay
+        world.spawn_fruit(circle(1.0, 1.0, settings::FRUIT_RADIUS));
+        assert_eq!(fruits(&mut world, &mut rng), 0);
+        assert_eq!(world.fruits().count(), settings::FRUITS as usize + 1);
+    }
+
+    #[test]
+    fn body_parts_spawn_on_the_map() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut players = Arena::new();
+        let owner = player(&mut players, "a");
+        let mut world = World::new(200, 100);
+
+        for _ in 0..50 {
+            let spawned = body_part(&mut world, owner, &mut rng);
+            let body_part = world.circle(spawned).unwrap();
+            assert!(body_part.center.x < 200.0 && body_part.center.y < 100.0);
+            assert_eq!(body_part.radius, settings::INITIAL_PLAYER_SCORE);
+        }
+        assert_eq!(world.cells().filter(|(_, cell_owner, _)| *cell_owner == owner).count(), 50);
+    }
+}
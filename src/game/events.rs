@@ -0,0 +1,113 @@
+use std::sync::mpsc::{self, Sender, Receiver};
+
+use crate::geometry::Position;
+
+use super::Game;
+use super::ids::{CellId, FruitId, PlayerId};
+
+// Something that happened in the simulation, snapshots only show its outcome
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    PlayerJoined {
+        player_id: PlayerId,
+        name: String,
+        bot: bool,
+    },
+    // Removed from the map, always follows PlayerDied for players who died
+    PlayerLeft {
+        player_id: PlayerId,
+    },
+    FruitEaten {
+        player_id: PlayerId,
+        fruit_id: FruitId,
+        position: Position,
+    },
+    CellEaten {
+        eater_id: PlayerId,
+        eater_cell_id: CellId,
+        eaten_id: PlayerId,
+        eaten_cell_id: CellId,
+        position: Position,
+        mass: u32,
+    },
+    // Lost its last body part, either eaten or killed from the console (no killer then)
+    PlayerDied {
+        player_id: PlayerId,
+        name: String,
+        killer_id: Option<PlayerId>,
+        killer_name: Option<String>,
+    },
+    // `body_parts` is how many the player has once split
+    Split {
+        player_id: PlayerId,
+        body_parts: usize,
+    },
+    // `body_parts` is how many the player has once merged, so always 1 for now
+    Merge {
+        player_id: PlayerId,
+        body_parts: usize,
+    },
+}
+
+// Every event of a tick, in the order they happened
+#[derive(Clone, Debug)]
+pub struct TickEvents {
+    pub tick: u64,
+    pub events: Vec<GameEvent>,
+}
+
+// Collects the events of the current tick and hands them out to every subscriber once it's over
+#[derive(Default)]
+pub struct EventBus {
+    pending: Vec<GameEvent>,
+    subscribers: Vec<Sender<TickEvents>>,
+}
+
+// Snapshots sent to the UI are clones of the game, they don't publish anything
+impl Clone for EventBus {
+    fn clone(&self) -> EventBus {
+        return EventBus::default();
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self) -> Receiver<TickEvents> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+
+        return rx;
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        // Nobody would ever see it
+        if self.subscribers.is_empty() {
+            return;
+        }
+
+        self.pending.push(event);
+    }
+
+    pub fn publish(&mut self, tick: u64) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let events = std::mem::take(&mut self.pending);
+        // Subscribers who dropped their receiver are forgotten
+        self.subscribers.retain(|subscriber| {
+            subscriber
+                .send(TickEvents {
+                    tick,
+                    events: events.clone(),
+                })
+                .is_ok()
+        });
+    }
+}
+
+impl Game {
+    // Receives the events of every tick from now on, until the receiver is dropped
+    pub fn subscribe(&mut self) -> Receiver<TickEvents> {
+        return self.events.subscribe();
+    }
+}
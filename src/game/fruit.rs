@@ -8,7 +8,7 @@ pub type Fruit = Circle;
 
 impl Fruit {
 
-    pub fn new(rng: &mut rand::prelude::ThreadRng, map_width: u32, map_height: u32) -> Fruit {
+    pub fn new(rng: &mut impl Rng, map_width: u32, map_height: u32) -> Fruit {
         Fruit {
             center: Position {
                 x: rng.gen_range(0..map_width) as f32,
@@ -0,0 +1,38 @@
+use std::fmt;
+
+use super::arena::{Index, Key};
+
+// Compact, copyable handles to the players and entities of the game, handed out by game::map and game::ecs.
+// Written as `slot.generation`, e.g. `4.1`.
+macro_rules! id_type {
+    ($name:ident) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(Index);
+
+        impl Key for $name {
+            fn from_index(index: Index) -> $name {
+                return $name(index);
+            }
+
+            fn index(&self) -> Index {
+                return self.0;
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}.{}", self.0.slot, self.0.generation)
+            }
+        }
+    };
+}
+
+// A player, valid until they leave or die
+id_type!(PlayerId);
+// Anything living in the world, see game::ecs
+id_type!(Entity);
+
+// Body parts and fruits are both entities, the names only tell which one is expected.
+// A body part survives moving and growing but not being merged or eaten.
+pub type CellId = Entity;
+pub type FruitId = Entity;
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::time;
+use crate::settings;
+use crate::geometry::Position;
+
+use super::ids::PlayerId;
+
+#[derive(Clone, Copy)]
+struct Bucket {
+    tokens: f32,
+    last_refill_timestamp: u128,
+}
+
+// Token bucket per player: every input costs a token and tokens refill at
+// settings::MAX_PLAYER_INPUTS_PER_SECOND, so short bursts are fine but floods are not.
+#[derive(Clone, Default)]
+pub struct InputLimiter {
+    buckets: HashMap<PlayerId, Bucket>,
+    // Latest steering of each player that is still waiting to be applied
+    pending_moves: HashMap<PlayerId, Position>,
+    // Inputs thrown away because the player was over the limit
+    pub dropped: u64,
+    // Steering updates replaced by a newer one before being applied
+    pub merged: u64,
+}
+
+impl InputLimiter {
+    pub fn allow(&mut self, player_id: PlayerId) -> bool {
+        return self.allow_at(player_id, time::now());
+    }
+
+    // Same as allow with the clock given, `now` in nanoseconds
+    fn allow_at(&mut self, player_id: PlayerId, now: u128) -> bool {
+        let rate = settings::MAX_PLAYER_INPUTS_PER_SECOND as f32;
+
+        let bucket = self.buckets
+            .entry(player_id)
+            .or_insert(Bucket {
+                tokens: rate,
+                last_refill_timestamp: now,
+            });
+
+        let elapsed = now.saturating_sub(bucket.last_refill_timestamp) as f32 / 1_000_000_000.0;
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.last_refill_timestamp = now;
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+
+        bucket.tokens -= 1.0;
+        return true;
+    }
+
+    pub fn drop_input(&mut self) {
+        self.dropped += 1;
+    }
+
+    // Drops everything kept about a player who left the game
+    pub fn forget(&mut self, player_id: PlayerId) {
+        self.buckets.remove(&player_id);
+        self.pending_moves.remove(&player_id);
+    }
+
+    pub fn queue_move(&mut self, player_id: PlayerId, direction: Position) {
+        if self.pending_moves.insert(player_id, direction).is_some() {
+            self.merged += 1;
+        }
+    }
+
+    // Hands out the queued steering of every player that is allowed to move now,
+    // the others keep theirs queued for the next frame.
+    pub fn take_moves(&mut self) -> Vec<(PlayerId, Position)> {
+        return self.take_moves_at(time::now());
+    }
+
+    fn take_moves_at(&mut self, now: u128) -> Vec<(PlayerId, Position)> {
+        let player_ids: Vec<PlayerId> = self.pending_moves.keys().copied().collect();
+        let mut moves = vec![];
+
+        for player_id in player_ids {
+            if self.allow_at(player_id, now) {
+                if let Some(direction) = self.pending_moves.remove(&player_id) {
+                    moves.push((player_id, direction));
+                }
+            }
+        }
+
+        return moves;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{arena::Arena, ids::PlayerId};
+
+    const SECOND: u128 = 1_000_000_000;
+
+    fn players(count: usize) -> Vec<PlayerId> {
+        let mut arena: Arena<PlayerId, ()> = Arena::new();
+        return (0..count).map(|_| arena.insert(())).collect();
+    }
+
+    #[test]
+    fn bursts_up_to_the_rate_then_drops() {
+        let rate = settings::MAX_PLAYER_INPUTS_PER_SECOND as usize;
+        let player = players(1)[0];
+        let mut limiter = InputLimiter::default();
+
+        let allowed = (0..rate * 2).filter(|_| limiter.allow_at(player, SECOND)).count();
+        assert_eq!(allowed, rate);
+        assert!(!limiter.allow_at(player, SECOND));
+    }
+
+    #[test]
+    fn refills_with_time_up_to_the_rate() {
+        let rate = settings::MAX_PLAYER_INPUTS_PER_SECOND as u128;
+        let player = players(1)[0];
+        let mut limiter = InputLimiter::default();
+
+        while limiter.allow_at(player, SECOND) {}
+
+        // One token back every 1/rate seconds
+        let one_token = SECOND / rate + 1;
+        assert!(limiter.allow_at(player, SECOND + one_token));
+        assert!(!limiter.allow_at(player, SECOND + one_token));
+
+        // A long wait doesn't save up more than a second's worth
+        let later = SECOND * 60;
+        let allowed = (0..rate * 2).filter(|_| limiter.allow_at(player, later)).count();
+        assert_eq!(allowed as u128, rate);
+    }
+
+    #[test]
+    fn players_have_their_own_bucket() {
+        let [a, b] = players(2)[..] else { unreachable!() };
+        let mut limiter = InputLimiter::default();
+
+        while limiter.allow_at(a, SECOND) {}
+        assert!(limiter.allow_at(b, SECOND));
+
+        // Forgotten players start over with a full bucket
+        limiter.forget(a);
+        assert!(limiter.allow_at(a, SECOND));
+    }
+
+    #[test]
+    fn merges_queued_moves_into_the_latest() {
+        let [a, b] = players(2)[..] else { unreachable!() };
+        let mut limiter = InputLimiter::default();
+
+        limiter.queue_move(a, Position::new(1.0, 0.0));
+        limiter.queue_move(a, Position::new(2.0, 0.0));
+        limiter.queue_move(a, Position::new(3.0, 0.0));
+        limiter.queue_move(b, Position::new(0.0, 1.0));
+        assert_eq!(limiter.merged, 2);
+
+        let mut moves = limiter.take_moves_at(SECOND);
+        moves.sort_by_key(|(player_id, _)| *player_id != a);
+        assert_eq!(moves, vec![(a, Position::new(3.0, 0.0)), (b, Position::new(0.0, 1.0))]);
+        assert!(limiter.take_moves_at(SECOND).is_empty());
+    }
+
+    #[test]
+    fn moves_over_the_limit_wait_for_the_next_frame() {
+        let player = players(1)[0];
+        let mut limiter = InputLimiter::default();
+
+        while limiter.allow_at(player, SECOND) {}
+        limiter.queue_move(player, Position::new(1.0, 1.0));
+
+        assert!(limiter.take_moves_at(SECOND).is_empty());
+        assert_eq!(limiter.take_moves_at(SECOND * 2), vec![(player, Position::new(1.0, 1.0))]);
+    }
+}
@@ -1,31 +1,38 @@
-use std::collections::HashMap;
-
 use crate::settings;
-use crate::{geometry, geometry::Position, geometry::circle::Circle};
+use crate::{geometry, geometry::Position, geometry::circle::Circle, geometry::shape::Shape};
+use crate::geometry::rectangle::{Rectangle, RectangleSize, Size};
 
-use super::{fruit::Fruit, player::Player};
+use super::{fruit::Fruit, obstacle::Obstacle, player::{Cell, Player}};
+use super::arena::Arena;
+use super::ecs::{World, systems::spawning};
+use super::ids::PlayerId;
 
 #[derive(Clone)]
 pub struct Map {
     pub width: u32,
     pub height: u32,
-    pub players: HashMap<String, Player>,
-    pub fruits: Vec<Fruit>,
+    pub players: Arena<PlayerId, Player>,
+    // The players' body parts and the fruits
+    pub world: World,
+    pub obstacles: Vec<Obstacle>,
 }
 
 impl Map {
     pub fn new() -> Map {
         let width = settings::MAP_WIDTH;
         let height = settings::MAP_HEIGHT;
-        let fruits = Fruit::generate_many(settings::FRUITS, width, height);
-        let players = HashMap::new();
+        let mut world = World::new(width, height);
+        spawning::fruits(&mut world, &mut rand::thread_rng());
+        let players = Arena::new();
+        let obstacles = Obstacle::init();
 
         return Map {
-            fruits,
+            world,
+            obstacles,
             players,
             width,
             height,
-        }
+        };
     }
 }
 
@@ -33,57 +40,64 @@ pub type MapView = geometry::rectangle::Rectangle;
 
 impl MapView {
 
+    // The area outside of which nothing is drawn, a bit bigger than the view so cells don't pop in at the edges
+    pub fn culling_rectangle(&self) -> Rectangle {
+        let size = Rectangle::to_rectangle_size(self.size.clone());
+        let margin = settings::CULLING_MARGIN * 2;
+
+        Rectangle {
+            position: self.position,
+            size: Size::Rectangle(RectangleSize {
+                width: size.width + margin,
+                height: size.height + margin,
+            }),
+        }
+    }
+
     pub fn is_visible(&self, circle: Circle) -> bool {
-        self.contains_position(circle.center) || circle.holds(self.closest_position_within(circle.center))
+        self.culling_rectangle().intersects(&circle)
     }
 
-    pub fn map_position(&self, position: Position) -> Position {
-        let top_left = self.get_corners().top_left;
+    pub fn is_player_visible(&self, player: &Player, map: &Map) -> bool {
+        !player.paused && player.cells(&map.world).iter().any(|body_part| self.is_visible(body_part.circle))
+    }
 
-        let mapped_pos = Position {
-            x: position.x - top_left.x,
-            y: position.y - top_left.y,
-        };
+    pub fn map_position(&self, position: Position) -> Position {
+        return position - self.get_corners().top_left;
+    }
 
-        return mapped_pos;
+    // Inverse of map_position, from screen coordinates back to the map
+    pub fn world_position(&self, position: Position) -> Position {
+        return position + self.get_corners().top_left;
     }
 
     pub fn get_visible_fruits(&self, map: &Map) -> Vec<Fruit> {
-        map.fruits
-            .iter()
-            .cloned()
-            .filter(|fruit| self.is_visible(fruit.clone()))
+        map.world
+            .fruits()
+            .map(|(_, fruit)| fruit)
+            .filter(|fruit| self.is_visible(*fruit))
             .collect()
     }
-    pub fn get_visible_players(&self, map: &Map) -> Vec<Player> {
+
+    // Each visible player with its body parts mapped to the screen
+    pub fn get_visible_players(&self, map: &Map) -> Vec<(Player, Vec<Cell>)> {
         map.players
             .values()
-            .cloned()
-            .filter(|player| {
-                let body_parts = player.body_parts.clone();
-                for body_part in body_parts {
-                    if self.is_visible(body_part) {
-                        return true;
-                    }
-                }
-
-                return false;
-            })
-            .map(|player| {
-                let body_parts = player.body_parts.clone();
-                let mapped_body_parts = body_parts
-                    .iter()
-                    .map(|body_part| Circle {
-                        center: self.map_position(body_part.center),
-                        ..*body_part
-                    })
-                    .collect();
-
-
-                return Player {
-                    body_parts: mapped_body_parts,
-                    ..player
-                };
+            .filter(|player| self.is_player_visible(player, map))
+            .map(|player| (player.clone(), self.map_player(player, map)))
+            .collect()
+    }
+
+    pub fn map_player(&self, player: &Player, map: &Map) -> Vec<Cell> {
+        player
+            .cells(&map.world)
+            .into_iter()
+            .map(|body_part| Cell {
+                circle: Circle {
+                    center: self.map_position(body_part.center),
+                    ..body_part.circle
+                },
+                ..body_part
             })
             .collect()
     }
@@ -1,20 +1,40 @@
-use std::sync::mpsc::{Sender, Receiver};
+use std::collections::HashMap;
+use std::sync::mpsc::{Sender, Receiver, TryRecvError};
 
-use rand::prelude::thread_rng;
+use rand::prelude::*;
 
 use crate::time;
 use crate::settings;
+use crate::logging::{self, Level};
+use crate::{info, debug, trace};
+use crate::config::Config;
+use crate::error::{GameError, TransportError};
 use crate::helper::{G2UMessage, U2GMessage, PlayerEvent};
-use crate::geometry::{Position, rectangle::{Rectangle, RectangleSize, Size}};
+use crate::geometry::Position;
 
 // Mods
+pub mod arena;
+pub mod ids;
+pub mod ecs;
 pub mod map;
 pub mod player;
+pub mod bots;
+pub mod chat;
+pub mod console;
+pub mod events;
 pub mod fruit;
 pub mod obstacle;
-
+pub mod input_limiter;
+pub mod spatial;
 
 use map::Map;
+use ecs::{World, systems::{collision, decay, movement, spawning}};
+use input_limiter::InputLimiter;
+use chat::ChatLimiter;
+use console::Issuer;
+use events::{EventBus, GameEvent};
+use ids::PlayerId;
+use player::{JoinRequest, Player};
 
 #[derive(Clone)]
 pub enum Status {
@@ -23,22 +43,89 @@ pub enum Status {
     Closed,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    // Pausing stops the whole simulation
+    Local,
+    // Pausing only freezes the player who asked for it
+    Multiplayer,
+}
+
+// How long the last frame spent on each part of the simulation, in nanoseconds
+#[derive(Clone, Copy, Default)]
+pub struct Timings {
+    pub tick: u128,
+    pub movement: u128,
+    pub collisions: u128,
+    pub snapshot: u128,
+}
+
 #[derive(Clone)]
 pub struct Game {
     pub map: Map,
     pub status: Status,
+    pub mode: Mode,
+    pub input_limiter: InputLimiter,
+    pub chat_limiter: ChatLimiter,
+    // Simulation steps run so far and when the last one ran
+    pub tick: u64,
+    pub tick_timestamp: u128,
+    pub timings: Timings,
     pub last_frame_timestamp: u128,
     pub fps: u16,
+    // Simulation steps per second the game aims for
+    pub tick_rate: u16,
+    // Secrets a player can `login` with to run console commands in multiplayer
+    pub operator_tokens: Vec<String>,
+    // Players who logged in and the token they used, until they leave or the token is revoked
+    pub operators: HashMap<PlayerId, String>,
+    pub events: EventBus,
+    // How many threads the movement and collision systems may use, 1 (the default) runs everything
+    // on the game thread. More is opt-in until the speedup has been measured on several cores
+    pub threads: usize,
 }
 
 impl Game {
-    pub fn new() -> Game {
+    pub fn new(mode: Mode) -> Game {
         return Game {
             map: Map::new(),
             status: Status::Running,
+            mode,
+            input_limiter: InputLimiter::default(),
+            chat_limiter: ChatLimiter::default(),
+            tick: 0,
+            tick_timestamp: time::now(),
+            timings: Timings::default(),
             last_frame_timestamp: time::now(),
             fps: 0,
+            tick_rate: settings::MAX_FPS,
+            operator_tokens: vec![],
+            operators: HashMap::new(),
+            events: EventBus::default(),
+            threads: 1,
+        };
+    }
+
+    pub fn apply_config(&mut self, config: &Config) {
+        self.tick_rate = config
+            .get("game.tick_rate")
+            .and_then(|tick_rate| tick_rate.parse().ok())
+            .unwrap_or(settings::MAX_FPS)
+            .clamp(1, settings::MAX_TICK_RATE);
+
+        // 0 uses every core
+        self.threads = match config.get("game.threads").and_then(|threads| threads.parse().ok()) {
+            Some(0) => ecs::parallel::default_threads(),
+            Some(threads) => threads,
+            None => 1,
         };
+
+        self.operator_tokens = config
+            .get_list("console.operator_tokens")
+            .unwrap_or_default()
+            .into_iter()
+            .map(|token| token.to_string())
+            .collect();
     }
 
     fn update_fps(&mut self) {
@@ -46,91 +133,187 @@ impl Game {
         self.last_frame_timestamp = time::now();
     }
 
-    fn moving(&mut self, player_id: String, direction: Position) {
-        let mut player = player::Player::get(Some(player_id.clone()), self).unwrap();
-
-        player.direction = direction;
-
-        *self.map.players.get_mut(&player_id).unwrap() = player;
+    fn moving(&mut self, player_id: PlayerId, direction: Position) {
+        // The player may have been killed since the steering was queued
+        if let Some(player) = self.map.players.get_mut(player_id) {
+            player.direction = direction;
+        }
     }
 
-    fn move_players(&mut self) {
-        let players = self.map.players.values_mut();
-
-        for player in players {
-            let player_score = player.get_score();
-            for body_part in player.body_parts.iter_mut() {
-                let delta = self.fps as f32 / (10.0 * player_score as f32);
-
-                let future_position = Game::future_position(
-                    body_part.center, Position {
-                        x: player.direction.x * delta,
-                        y: player.direction.y * delta,
-                    });
-                
-                let map_rect = Rectangle {
-                    position: Position {
-                        x: settings::MAP_WIDTH as f32 / 2.0,
-                        y: settings::MAP_HEIGHT as f32 / 2.0,
-                    },
-                    size: Size::Rectangle(RectangleSize {
-                        width: settings::MAP_WIDTH,
-                        height: settings::MAP_HEIGHT,
-                    }),
-                };
-
-                if map_rect.contains_position(future_position) {
-                    body_part.center = future_position;
+    fn set_paused(&mut self, player_id: PlayerId, paused: bool) {
+        match self.mode {
+            Mode::Local => {
+                self.status = if paused { Status::Paused } else { Status::Running };
+            }
+            Mode::Multiplayer => {
+                if let Some(player) = self.map.players.get_mut(player_id) {
+                    player.paused = paused;
                 }
             }
         }
     }
 
-    fn future_position(position: Position, speed: Position) -> Position {
-        Position {
-            x: position.x + speed.x,
-            y: position.y + speed.y,
+    fn split(&mut self, player_id: PlayerId) {
+        if let Some(player) = self.map.players.get_mut(player_id) {
+            let body_parts = player.body_parts.len();
+            player.split(&mut self.map.world);
+
+            if player.body_parts.len() > body_parts {
+                self.events.emit(GameEvent::Split {
+                    player_id,
+                    body_parts: player.body_parts.len(),
+                });
+            }
         }
-    } 
+    }
 
-    fn check_fruit_collision(&mut self) {
-        let mut fruits = self.map.fruits.clone();
-        let players = self.map.players.values_mut();
+    // Spawns the player somewhere on the map with a single body part
+    pub fn add_player(&mut self, join: JoinRequest) -> PlayerId {
+        let world = &mut self.map.world;
+        let player_id = self.map.players.insert_with(|player_id| {
+            let mut player = Player::new(player_id, join);
+            player.body_parts.push(spawning::body_part(world, player_id, &mut thread_rng()));
+            player
+        });
+
+        if let Some(player) = self.map.players.get(player_id) {
+            info!("player joined", player_id = player_id, name = player.name, bot = player.bot);
+            self.events.emit(GameEvent::PlayerJoined {
+                player_id,
+                name: player.name.clone(),
+                bot: player.bot,
+            });
+        }
 
-        for player in players {
-            for body_part in player.body_parts.iter_mut() {
-                fruits = fruits.iter()
-                    .cloned()
-                    .map(|fruit| {
-                        if body_part.holds(fruit.center) {
-                            body_part.radius += fruit.radius / 10;
-                            let mut rng = thread_rng();
-                            return fruit::Fruit::new(&mut rng, settings::MAP_WIDTH, settings::MAP_HEIGHT);
-                        }
+        return player_id;
+    }
 
-                        return fruit;
-                    })
-                    .collect();
-            }
+    pub fn remove_player(&mut self, player_id: PlayerId, reason: &str) -> Option<Player> {
+        let player = self.map.players.remove(player_id)?;
+        for body_part in player.body_parts.iter() {
+            self.map.world.despawn(*body_part);
+        }
+        self.input_limiter.forget(player_id);
+        self.chat_limiter.forget(player_id);
+        self.operators.remove(&player_id);
+
+        info!("player left", player_id = player_id, name = player.name, reason = reason);
+        self.events.emit(GameEvent::PlayerLeft {
+            player_id,
+        });
+
+        return Some(player);
+    }
+
+    fn eject(&mut self, player_id: PlayerId) {
+        if let Some(player) = self.map.players.get(player_id) {
+            player.eject(&mut self.map.world);
         }
+    }
+
+    fn merge_players(&mut self) {
+        for player in self.map.players.values_mut() {
+            let body_parts = player.body_parts.len();
+            player.merge(&mut self.map.world);
 
-        self.map.fruits = fruits;
+            if player.body_parts.len() < body_parts {
+                self.events.emit(GameEvent::Merge {
+                    player_id: player.id,
+                    body_parts: player.body_parts.len(),
+                });
+            }
+        }
     }
-    fn check_player_collision(&mut self) {
 
+    fn move_players(&mut self) {
+        movement::steer(&mut self.map.world, &self.map.players, self.tick_rate);
+        movement::run(&mut self.map.world, self.threads);
     }
 
-    fn check_object_collision(&mut self) {
-        self.check_fruit_collision();
+    // How far the player moves in a tick when the game runs at `tick_rate`. Taken from the
+    // configured rate rather than the measured fps so update works the same outside of init
+    pub fn player_step(tick_rate: u16, player: &player::Player, world: &World) -> Position {
+        let delta = tick_rate as f32 / (10.0 * player.get_score(world) as f32);
+        return player.step(delta);
     }
 
+    // Whatever got eaten goes out as events, players who lost their last body part die
     fn check_collision(&mut self) {
-        self.check_object_collision();
+        let meals = {
+            let _span = logging::span(Level::Trace, "collisions");
+            collision::run(&mut self.map.world, &self.map.players, self.threads)
+        };
+
+        if meals.is_empty() {
+            return;
+        }
+
+        let world = &self.map.world;
+        for player in self.map.players.values_mut() {
+            player.body_parts.retain(|body_part| world.contains(*body_part));
+        }
+
+        let mut eaten_fruits = 0;
+        for meal in meals {
+            let cell_eaten = match &meal {
+                GameEvent::CellEaten { eaten_id, eater_id, .. } => Some((*eaten_id, *eater_id)),
+                _ => None,
+            };
+            self.events.emit(meal);
+
+            let (prey_id, eater_id) = match cell_eaten {
+                Some(cell_eaten) => cell_eaten,
+                None => {
+                    eaten_fruits += 1;
+                    continue;
+                }
+            };
+
+            let prey = match self.map.players.get(prey_id) {
+                Some(prey) if prey.body_parts.is_empty() => prey,
+                _ => continue,
+            };
+
+            let name = prey.name.clone();
+            let killer_name = self.map.players.get(eater_id).map(|killer| killer.name.clone());
+
+            debug!("player eaten", player_id = prey_id, killer_id = eater_id);
+            self.events.emit(GameEvent::PlayerDied {
+                player_id: prey_id,
+                name,
+                killer_id: Some(eater_id),
+                killer_name,
+            });
+            self.remove_player(prey_id, "eaten");
+        }
+
+        if eaten_fruits > 0 {
+            trace!("fruits eaten", count = eaten_fruits);
+        }
     }
 
-    fn update(&mut self) {
+    // Advances the simulation by one tick, init calls it at the configured tick rate
+    pub fn update(&mut self) {
+        let _span = logging::span(Level::Trace, "tick");
+        let tick_timestamp = time::now();
+
+        self.steer_bots();
         self.move_players();
+        self.merge_players();
+        decay::run(&mut self.map.world, &self.map.players, self.tick);
+        let movement_timestamp = time::now();
+
         self.check_collision();
+        spawning::fruits(&mut self.map.world, &mut thread_rng());
+        let collisions_timestamp = time::now();
+
+        self.timings.movement = movement_timestamp - tick_timestamp;
+        self.timings.collisions = collisions_timestamp - movement_timestamp;
+        self.timings.tick = collisions_timestamp - tick_timestamp;
+
+        self.events.publish(self.tick);
+        self.tick += 1;
+        self.tick_timestamp = collisions_timestamp;
     }
 
     pub fn get_fps(last_frame_timestamp: u128) -> u16 {
@@ -138,26 +321,81 @@ impl Game {
         return fps;
     }
 
-    fn get_inputs(&mut self, rx: &Receiver<U2GMessage>) {
-        let rx_message = rx.try_iter();
+    fn get_inputs(&mut self, tx: &Sender<G2UMessage>, rx: &Receiver<U2GMessage>, console: Option<&Receiver<String>>) -> Result<(), GameError> {
+        let _span = logging::span(Level::Trace, "inputs");
+
+        // A closed terminal only stops the commands, the game goes on
+        if let Some(console) = console {
+            while let Ok(line) = console.try_recv() {
+                self.run_command(Issuer::Console, line, tx)?;
+            }
+        }
+
+        loop {
+            let message = match rx.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err(TransportError::UiDisconnected.into()),
+            };
 
-        for message in rx_message {
             match message {
-                U2GMessage::NewPlayer(player) => {
-                    self.map.players.insert(player.id.clone(), player);
+                U2GMessage::Join(join) => {
+                    let player_id = self.add_player(join);
+                    tx.send(G2UMessage::Joined(player_id))?;
+                }
+                U2GMessage::PlayerEvent(player_id, PlayerEvent::Moving(direction)) => {
+                    // Only the latest steering matters, it's applied below
+                    self.input_limiter.queue_move(player_id, direction);
                 }
                 U2GMessage::PlayerEvent(player_id, event) => {
+                    if !self.input_limiter.allow(player_id) {
+                        debug!("input dropped", player_id = player_id, reason = "rate limited");
+                        self.input_limiter.drop_input();
+                        continue;
+                    }
+
                     match event {
-                        PlayerEvent::Moving(direction) => {
-                            self.moving(player_id, direction);
+                        PlayerEvent::Split => {
+                            self.split(player_id);
                         }
+                        PlayerEvent::Eject => {
+                            self.eject(player_id);
+                        }
+                        PlayerEvent::Moving(_) => {}
+                    }
+                }
+                U2GMessage::Pause(player_id) => {
+                    debug!("paused", player_id = player_id);
+                    self.set_paused(player_id, true);
+                }
+                U2GMessage::Resume(player_id) => {
+                    debug!("resumed", player_id = player_id);
+                    self.set_paused(player_id, false);
+                }
+                U2GMessage::Chat(player_id, text) => {
+                    self.chat(player_id, text, tx)?;
+                }
+                U2GMessage::Command(player_id, line) => {
+                    self.run_command(Issuer::Player(player_id), line, tx)?;
+                }
+                U2GMessage::Step => {
+                    if let Status::Paused = self.status {
+                        debug!("stepping one tick", tick = self.tick);
+                        self.update();
                     }
                 }
                 U2GMessage::Quit => {
+                    info!("quit requested", tick = self.tick);
                     self.status = Status::Closed;
                 }
             }
         }
+
+        for (player_id, direction) in self.input_limiter.take_moves() {
+            self.moving(player_id, direction);
+        }
+
+        return Ok(());
     }
 
     pub fn delay_fps(last_frame_timestamp: u128, max_fps: u16) {
@@ -172,19 +410,43 @@ impl Game {
         }
     }
 
-    pub fn init(&mut self, tx: &Sender<G2UMessage>, rx: &Receiver<U2GMessage>) {
+    // Runs until a player quits, or fails once nobody is listening anymore
+    pub fn init(&mut self, tx: &Sender<G2UMessage>, rx: &Receiver<U2GMessage>) -> Result<(), GameError> {
+        return self.run(tx, rx, None);
+    }
+
+    // Same as init, the lines sent on `console` run as Issuer::Console, which may run any command.
+    // Only the process hosting the game can hand one over, e.g. the terminal of agario-server
+    pub fn init_with_console(&mut self, tx: &Sender<G2UMessage>, rx: &Receiver<U2GMessage>, console: &Receiver<String>) -> Result<(), GameError> {
+        return self.run(tx, rx, Some(console));
+    }
+
+    fn run(&mut self, tx: &Sender<G2UMessage>, rx: &Receiver<U2GMessage>, console: Option<&Receiver<String>>) -> Result<(), GameError> {
+        let _span = logging::span(Level::Info, "game");
+        info!(
+            "game started",
+            mode = if self.mode == Mode::Local { "local" } else { "multiplayer" },
+            tick_rate = self.tick_rate,
+            fruits = self.map.world.fruits().count(),
+        );
+
         'main_loop: loop {
             match self.status {
                 Status::Running | Status::Paused => {
-                    self.get_inputs(rx);
+                    self.get_inputs(tx, rx, console)?;
                     
                     if let Status::Running = &self.status {
                         self.update()
                     }
+                    // Whatever the inputs did still goes out while paused
+                    self.events.publish(self.tick);
                     
-                    tx.send(G2UMessage::StateUpdate(self.clone())).unwrap();
+                    let snapshot_timestamp = time::now();
+                    tx.send(G2UMessage::StateUpdate(Box::new(self.clone())))?;
+                    // Shows up in the next snapshot
+                    self.timings.snapshot = time::now() - snapshot_timestamp;
 
-                    Game::delay_fps(self.last_frame_timestamp, settings::MAX_FPS + 1);
+                    Game::delay_fps(self.last_frame_timestamp, self.tick_rate + 1);
                     self.update_fps();
                 }
                 _ => {
@@ -192,5 +454,156 @@ impl Game {
                 }
             }
         }
+
+        info!("game stopped", ticks = self.tick, players = self.map.players.len());
+        return Ok(());
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::circle::Circle;
+
+    fn join(name: &str) -> JoinRequest {
+        return JoinRequest {
+            name: name.to_string(),
+            color: None,
+            skin: None,
+            bot: false,
+        };
+    }
+
+    fn place(game: &mut Game, player_id: PlayerId, x: f32, radius: u32) {
+        let body_part = game.map.players.get(player_id).unwrap().body_parts[0];
+        game.map.world.set_circle(body_part, Circle {
+            center: Position { x, y: 500.0 },
+            radius,
+        });
+    }
+
+    #[test]
+    fn eaten_players_die_and_leave() {
+        let mut game = Game::new(Mode::Multiplayer);
+        let events = game.subscribe();
+        let eater = game.add_player(join("eater"));
+        let prey = game.add_player(join("prey"));
+        place(&mut game, eater, 500.0, 60);
+        place(&mut game, prey, 520.0, 20);
+
+        game.update();
+
+        assert!(!game.map.players.contains(prey));
+        assert_eq!(game.map.world.cells().count(), 1);
+        assert_eq!(game.map.world.fruits().count(), settings::FRUITS as usize);
+
+        let events: Vec<GameEvent> = events.try_iter().flat_map(|tick_events| tick_events.events).collect();
+        let died = events.iter().find_map(|event| match event {
+            GameEvent::PlayerDied { player_id, killer_id, .. } => Some((*player_id, *killer_id)),
+            _ => None,
+        });
+        assert_eq!(died, Some((prey, Some(eater))));
+        assert!(events.iter().any(|event| matches!(event, GameEvent::PlayerLeft { player_id } if *player_id == prey)));
+    }
+
+    #[test]
+    fn update_moves_players_without_init() {
+        let mut game = Game::new(Mode::Local);
+        let player_id = game.add_player(join("a"));
+        place(&mut game, player_id, 500.0, 20);
+        game.map.players.get_mut(player_id).unwrap().direction = Position::new(settings::SPEED_DISTANCE_CAP, 0.0);
+
+        game.update();
+
+        let moved = game.map.players.get(player_id).unwrap().position(&game.map.world).unwrap();
+        let step = game.tick_rate as f32 / (10.0 * 20.0);
+        assert!((moved.x - (500.0 + step)).abs() < 1e-3);
+        assert_eq!(game.fps, 0);
+    }
+
+    #[test]
+    fn players_survive_until_their_last_body_part_is_eaten() {
+        let mut game = Game::new(Mode::Multiplayer);
+        let events = game.subscribe();
+        let eater = game.add_player(join("eater"));
+        let prey = game.add_player(join("prey"));
+        place(&mut game, eater, 500.0, 60);
+        place(&mut game, prey, 2000.0, 40);
+
+        // One body part of the prey within reach of the eater, the other far away
+        game.split(prey);
+        let body_parts = game.map.players.get(prey).unwrap().body_parts.clone();
+        game.map.world.set_circle(body_parts[1], Circle {
+            center: Position::new(520.0, 500.0),
+            radius: 20,
+        });
+
+        game.update();
+
+        let player = game.map.players.get(prey).unwrap();
+        assert_eq!(player.body_parts, vec![body_parts[0]]);
+        let events: Vec<GameEvent> = events.try_iter().flat_map(|tick_events| tick_events.events).collect();
+        assert!(events.iter().any(|event| matches!(event, GameEvent::CellEaten { eaten_cell_id, .. } if *eaten_cell_id == body_parts[1])));
+        assert!(!events.iter().any(|event| matches!(event, GameEvent::PlayerDied { .. })));
+
+        // Then the last one
+        let last = game.map.players.get(prey).unwrap().body_parts[0];
+        game.map.world.set_circle(last, Circle {
+            center: Position::new(510.0, 500.0),
+            radius: 20,
+        });
+        game.update();
+        assert!(!game.map.players.contains(prey));
+    }
+
+    #[test]
+    fn eaters_take_the_area_of_their_prey() {
+        let mut game = Game::new(Mode::Multiplayer);
+        let eater = game.add_player(join("eater"));
+        let prey = game.add_player(join("prey"));
+        place(&mut game, eater, 500.0, 60);
+        place(&mut game, prey, 520.0, 45);
+
+        game.update();
+
+        // 60² + 45² = 75²
+        let eater_body = game.map.players.get(eater).unwrap().body_parts[0];
+        assert_eq!(game.map.world.circle(eater_body).unwrap().radius, 75);
+        assert_eq!(game.map.players.len(), 1);
+    }
+
+    #[test]
+    fn split_and_merge_report_the_body_parts_left() {
+        let mut game = Game::new(Mode::Local);
+        let events = game.subscribe();
+        let player_id = game.add_player(join("a"));
+        place(&mut game, player_id, 500.0, 100);
+
+        game.split(player_id);
+        game.split(player_id);
+        game.map.players.get_mut(player_id).unwrap().merge_timestamp = Some(0);
+        game.merge_players();
+        game.events.publish(game.tick);
+
+        let counts: Vec<(&str, usize)> = events
+            .try_iter()
+            .flat_map(|tick_events| tick_events.events)
+            .filter_map(|event| match event {
+                GameEvent::Split { body_parts, .. } => Some(("split", body_parts)),
+                GameEvent::Merge { body_parts, .. } => Some(("merge", body_parts)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(counts, vec![("split", 2), ("split", 4), ("merge", 1)]);
+    }
+
+    #[test]
+    fn leaving_despawns_the_body_parts() {
+        let mut game = Game::new(Mode::Local);
+        let player_id = game.add_player(join("a"));
+        assert_eq!(game.map.world.cells().count(), 1);
+
+        game.remove_player(player_id, "left");
+        assert_eq!(game.map.world.cells().count(), 0);
+        assert!(game.map.players.is_empty());
+    }
+}
@@ -1,72 +1,342 @@
-use rand::prelude::*;
-use uuid::Uuid;
-
-use crate::helper::U2GMessage;
-use std::sync::mpsc::{Sender};
+use std::ops::{Deref, DerefMut};
 
+use crate::time;
 use crate::game::Game;
 use crate::geometry::{Position, circle::Circle};
 use crate::settings;
 
+use super::ecs::World;
+use super::ids::{CellId, PlayerId};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayerColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl PlayerColor {
+    // Picks a palette color from the player's ID, the same ID always gets the same color
+    pub fn from_id(id: PlayerId) -> PlayerColor {
+        // FNV-1a, stable everywhere unlike the std hasher
+        let mut hash: u32 = 0x811c9dc5;
+        for byte in id.to_string().as_bytes() {
+            hash ^= *byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+
+        return settings::PLAYER_COLORS[hash as usize % settings::PLAYER_COLORS.len()];
+    }
+
+    pub fn is_in_palette(&self) -> bool {
+        settings::PLAYER_COLORS.contains(self)
+    }
+}
+
+// A body part as read from the world: a circle that keeps its ID while it moves and grows
+#[derive(Clone, Copy, Debug)]
+pub struct Cell {
+    pub id: CellId,
+    pub circle: Circle,
+}
+
+impl Deref for Cell {
+    type Target = Circle;
+
+    fn deref(&self) -> &Circle {
+        return &self.circle;
+    }
+}
+
+impl DerefMut for Cell {
+    fn deref_mut(&mut self) -> &mut Circle {
+        return &mut self.circle;
+    }
+}
+
+// What a client sends to join, the game gives the player their ID and first body part
+#[derive(Clone, Debug)]
+pub struct JoinRequest {
+    pub name: String,
+    // Anything outside of settings::PLAYER_COLORS is replaced by a color picked from the ID
+    pub color: Option<PlayerColor>,
+    pub skin: Option<String>,
+    pub bot: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct Player {
-    pub body_parts: Vec<Circle>,
+    // The player's entities in the world, their position and size live there
+    pub body_parts: Vec<CellId>,
     pub name: String,
+    // Always one of settings::PLAYER_COLORS, enforced by the game when the player joins
+    pub color: PlayerColor,
+    // Name of the picture in settings::SPRITES_FOLDER_PATH drawn over the cells
+    pub skin: Option<String>,
+    // Where the player is steering to, relative to its first body part.
+    // The further away the faster it goes, up to settings::SPEED_DISTANCE_CAP
     pub direction: Position,
-    pub id: String,
+    pub id: PlayerId,
+    // Paused players don't move, can't eat and are hidden from everyone else
+    pub paused: bool,
+    // When the split body parts join back together
+    pub merge_timestamp: Option<u128>,
+    // Steered by the game itself, see game::bots
+    pub bot: bool,
 }
 
 impl Player {
-    pub fn new(name: &str, rng: &mut rand::prelude::ThreadRng) -> Player {
-        let id = Uuid::new_v4().to_string();
-        let body_parts = vec![
-            Circle {
-                center: Position {
-                    x: rng.gen_range(0..settings::MAP_WIDTH) as f32,
-                    y: rng.gen_range(0..settings::MAP_HEIGHT) as f32,
-                },
-                radius: settings::INITIAL_PLAYER_SCORE,
-            }
-        ];
-
-        let player = Player {
-            id: id.clone(),
-            body_parts,
-            direction: Position {
-                x: 0.0,
-                y: 0.0,
-            },
-            name: name.to_string(),
+    // Without any body part yet, see Game::add_player
+    pub fn new(id: PlayerId, join: JoinRequest) -> Player {
+        let color = join.color
+            .filter(|color| color.is_in_palette())
+            .unwrap_or_else(|| PlayerColor::from_id(id));
+
+        return Player {
+            id,
+            body_parts: vec![],
+            direction: Position::ZERO,
+            name: join.name,
+            color,
+            skin: join.skin,
+            paused: false,
+            merge_timestamp: None,
+            bot: join.bot,
         };
-        
-        return player;
     }
 
-    pub fn connect(&self, tx: &Sender<U2GMessage>) {
-        tx.send(U2GMessage::NewPlayer(self.clone())).unwrap();
+    pub fn get(id: Option<PlayerId>, game: &Game) -> Option<&Player> {
+        return game.map.players.get(id?);
     }
 
-    pub fn get(id: Option<String>, game: &Game) -> Option<Player> {
-        let player_id = match id.clone() {
-            Some(id) => id,
-            None => return None,
-        };
-        let player = match game.map.players.get(&player_id) {
-            Some(player) => player,
-            None => return None,
+    // Unit vector of the direction the player is heading, pointing right when standing still
+    pub fn heading(&self) -> Position {
+        if self.direction == Position::ZERO {
+            return Position::new(1.0, 0.0);
+        }
+
+        return self.direction.normalize();
+    }
+
+    // How much the player moves this frame, given how far it could move at full speed
+    pub fn step(&self, max_step: f32) -> Position {
+        let distance = self.direction.length();
+        if distance < settings::STEERING_DEAD_ZONE {
+            return Position::ZERO;
+        }
+
+        let speed = max_step * (distance / settings::SPEED_DISTANCE_CAP).min(1.0);
+        // Never overshoot the target so the cell can rest right under the cursor
+        let speed = speed.min(distance);
+
+        return self.direction / distance * speed;
+    }
+
+    // The body parts that are still in the world, in the order they were added
+    pub fn cells(&self, world: &World) -> Vec<Cell> {
+        self.body_parts
+            .iter()
+            .filter_map(|id| Some(Cell {
+                id: *id,
+                circle: world.circle(*id)?,
+            }))
+            .collect()
+    }
+
+    // Every body part big enough splits in two, the new halves are spawned in `world`
+    pub fn split(&mut self, world: &mut World) {
+        let heading = self.heading();
+        let body_parts_count = self.body_parts.len();
+        let mut new_body_parts = vec![];
+
+        for mut body_part in self.cells(world) {
+            if body_part.radius < settings::MIN_SPLIT_RADIUS
+            || body_parts_count + new_body_parts.len() >= settings::MAX_BODY_PARTS {
+                continue;
+            }
+
+            // Both halves keep the same total area
+            let radius = (body_part.radius as f32 / std::f32::consts::SQRT_2) as u32;
+            body_part.radius = radius;
+            world.set_circle(body_part.id, body_part.circle);
+
+            new_body_parts.push(world.spawn_cell(self.id, Circle {
+                center: body_part.center + heading * radius as f32 * 2.0,
+                radius,
+            }));
+        }
+
+        if new_body_parts.is_empty() {
+            return;
+        }
+
+        self.body_parts.append(&mut new_body_parts);
+        self.merge_timestamp = Some(time::now() + time::to_nano(settings::MERGE_DELAY_MS));
+    }
+
+    // Joins every body part into the biggest one once the merge delay is over,
+    // the others are despawned from `world`
+    pub fn merge(&mut self, world: &mut World) {
+        let merge_timestamp = match self.merge_timestamp {
+            Some(merge_timestamp) => merge_timestamp,
+            None => return,
         };
 
-        return Some(player.clone());
+        if time::now() < merge_timestamp {
+            return;
+        }
+
+        let body_parts = self.cells(world);
+        let area: f64 = body_parts
+            .iter()
+            .map(|body_part| (body_part.radius as f64).powi(2))
+            .sum();
+
+        let biggest = body_parts
+            .iter()
+            .max_by_key(|body_part| body_part.radius)
+            .cloned();
+
+        if let Some(mut biggest) = biggest {
+            for body_part in body_parts.iter().filter(|body_part| body_part.id != biggest.id) {
+                world.despawn(body_part.id);
+            }
+
+            biggest.radius = area.sqrt() as u32;
+            world.set_circle(biggest.id, biggest.circle);
+            self.body_parts = vec![biggest.id];
+        }
+
+        self.merge_timestamp = None;
+    }
+
+    // Every body part big enough loses some mass, which is shot ahead as a fruit spawned in `world`.
+    // Returns how many fruits were ejected.
+    pub fn eject(&self, world: &mut World) -> usize {
+        let heading = self.heading();
+        let mut ejected = 0;
+
+        for mut body_part in self.cells(world) {
+            if body_part.radius < settings::MIN_EJECT_RADIUS {
+                continue;
+            }
+
+            body_part.radius -= settings::EJECT_RADIUS_COST;
+            world.set_circle(body_part.id, body_part.circle);
+
+            let distance = body_part.radius as f32 + settings::FRUIT_RADIUS as f32 * 4.0;
+            world.spawn_fruit(Circle {
+                center: body_part.center + heading * distance,
+                radius: settings::FRUIT_RADIUS,
+            });
+            ejected += 1;
+        }
+
+        return ejected;
+    }
+
+    // Mass is radius² / 100, worked out in u64 so huge body parts don't overflow
+    pub fn mass(body_part: &Circle) -> u32 {
+        let radius = body_part.radius as u64;
+        return (radius * radius / 100).min(u32::MAX as u64) as u32;
+    }
+
+    // Radius of a body part weighing `mass`, the inverse of mass
+    pub fn radius(mass: u32) -> u32 {
+        return (mass as f64 * 100.0).sqrt() as u32;
     }
 
-    pub fn get_score(&self) -> u32 {
+    // Average radius of the body parts
+    pub fn get_score(&self, world: &World) -> u32 {
+        let body_parts = self.cells(world);
         let mut score = 0;
-        let body_parts = self.body_parts.clone();
 
-        for body_part in body_parts {
+        for body_part in body_parts.iter() {
             score += body_part.radius;
         }
 
-        return score / self.body_parts.len() as u32;
+        return score / body_parts.len().max(1) as u32;
     }
-}
\ No newline at end of file
+
+    // Center of the first body part, where the camera and the steering are anchored
+    pub fn position(&self, world: &World) -> Option<Position> {
+        let first = *self.body_parts.first()?;
+        return world.positions.get(first).copied();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::arena::Arena;
+
+    fn player_in(world: &mut World, radius: u32) -> Player {
+        let mut players = Arena::new();
+        let player_id = players.insert_with(|player_id| Player::new(player_id, JoinRequest {
+            name: String::from("a"),
+            color: None,
+            skin: None,
+            bot: false,
+        }));
+
+        let mut player = players.remove(player_id).unwrap();
+        player.body_parts.push(world.spawn_cell(player_id, Circle {
+            center: Position { x: 500.0, y: 500.0 },
+            radius,
+        }));
+        player.direction = Position { x: 10.0, y: 0.0 };
+
+        return player;
+    }
+
+    fn area(player: &Player, world: &World) -> u32 {
+        player.cells(world).iter().map(|body_part| body_part.radius.pow(2)).sum()
+    }
+
+    #[test]
+    fn split_then_merge_keeps_the_area() {
+        let mut world = World::new(1000, 1000);
+        let mut player = player_in(&mut world, 100);
+
+        player.split(&mut world);
+        let body_parts = player.cells(&world);
+        assert_eq!(body_parts.len(), 2);
+        // Spawned ahead, in the direction the player is heading
+        assert!(body_parts[1].center.x > body_parts[0].center.x);
+        // Radii are rounded down, a bit of area gets lost
+        assert!(area(&player, &world).abs_diff(100 * 100) <= 2 * 100);
+
+        // Not before the merge delay is over
+        player.merge(&mut world);
+        assert_eq!(player.body_parts.len(), 2);
+
+        player.merge_timestamp = Some(0);
+        let split_area = area(&player, &world);
+        player.merge(&mut world);
+        assert_eq!(player.body_parts.len(), 1);
+        assert_eq!(world.cells().count(), 1);
+        assert!(area(&player, &world).abs_diff(split_area) <= 2 * 100);
+    }
+
+    #[test]
+    fn small_body_parts_dont_split_or_eject() {
+        let mut world = World::new(1000, 1000);
+        let mut player = player_in(&mut world, settings::MIN_EJECT_RADIUS - 1);
+
+        player.split(&mut world);
+        assert_eq!(player.eject(&mut world), 0);
+        assert_eq!(world.len(), 1);
+    }
+
+    #[test]
+    fn eject_shoots_a_fruit_ahead() {
+        let mut world = World::new(1000, 1000);
+        let player = player_in(&mut world, 50);
+
+        assert_eq!(player.eject(&mut world), 1);
+        assert_eq!(player.cells(&world)[0].radius, 50 - settings::EJECT_RADIUS_COST);
+
+        let (_, fruit) = world.fruits().next().unwrap();
+        assert!(fruit.center.x > 500.0 + 50.0);
+        assert_eq!(fruit.radius, settings::FRUIT_RADIUS);
+    }
+}
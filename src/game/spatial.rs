@@ -0,0 +1,116 @@
+use crate::geometry::{Position, circle::Circle, shape::Shape, rectangle::{Rectangle, Size}};
+
+// Uniform grid over the map, each cell lists the indices (or IDs) of the circles whose center falls in it.
+// Lets the collision checks only look at the circles near a body part instead of all of them.
+#[derive(Clone, Debug)]
+pub struct SpatialGrid<T = usize> {
+    pub cell_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub cells: Vec<Vec<T>>,
+}
+
+impl SpatialGrid<usize> {
+    pub fn build(width: u32, height: u32, cell_size: u32, circles: &[Circle]) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(width, height, cell_size);
+
+        for (circle_i, circle) in circles.iter().enumerate() {
+            grid.insert(circle_i, circle.center);
+        }
+
+        return grid;
+    }
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    pub fn new(width: u32, height: u32, cell_size: u32) -> SpatialGrid<T> {
+        let cell_size = cell_size.max(1);
+        let columns = width.div_ceil(cell_size);
+        let rows = height.div_ceil(cell_size);
+
+        SpatialGrid {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![vec![]; (columns * rows) as usize],
+        }
+    }
+
+    // Same layout without any cell, they're allocated by the next clear
+    pub fn without_cells(&self) -> SpatialGrid<T> {
+        SpatialGrid {
+            cell_size: self.cell_size,
+            columns: self.columns,
+            rows: self.rows,
+            cells: vec![],
+        }
+    }
+
+    // Empties every cell but keeps their memory, for grids rebuilt every tick
+    pub fn clear(&mut self) {
+        self.cells.resize_with((self.columns * self.rows) as usize, Vec::new);
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+    }
+
+    fn cell_of(&self, position: Position) -> (u32, u32) {
+        let column = (position.x.max(0.0) as u32 / self.cell_size).min(self.columns - 1);
+        let row = (position.y.max(0.0) as u32 / self.cell_size).min(self.rows - 1);
+        return (column, row);
+    }
+
+    // Row major index of the cell `position` falls in, nearby positions get nearby indices
+    pub fn cell_index(&self, position: Position) -> usize {
+        let (column, row) = self.cell_of(position);
+        return (row * self.columns + column) as usize;
+    }
+
+    pub fn insert(&mut self, index: T, position: Position) {
+        let cell_index = self.cell_index(position);
+        self.cells[cell_index].push(index);
+    }
+
+    pub fn cell(&self, column: u32, row: u32) -> &[T] {
+        &self.cells[(row * self.columns + column) as usize]
+    }
+
+    // The area covered by a cell
+    pub fn cell_rectangle(&self, column: u32, row: u32) -> Rectangle {
+        let cell_size = self.cell_size as f32;
+
+        Rectangle {
+            position: Position::new((column as f32 + 0.5) * cell_size, (row as f32 + 0.5) * cell_size),
+            size: Size::Square(self.cell_size),
+        }
+    }
+
+    // Indices of every circle whose center may lie within `circle`
+    pub fn query(&self, circle: Circle) -> Vec<T> {
+        let radius = circle.radius as f32;
+        let (first_column, first_row) = self.cell_of(Position {
+            x: circle.center.x - radius,
+            y: circle.center.y - radius,
+        });
+        let (last_column, last_row) = self.cell_of(Position {
+            x: circle.center.x + radius,
+            y: circle.center.y + radius,
+        });
+
+        let mut indices = vec![];
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                // The corners of the range around a big circle often miss it. Positions off the map
+                // are kept in the cells along its edges, so those are always looked at
+                let edge = column == 0 || row == 0 || column == self.columns - 1 || row == self.rows - 1;
+                if !edge && !self.cell_rectangle(column, row).intersects(&circle) {
+                    continue;
+                }
+
+                indices.extend_from_slice(self.cell(column, row));
+            }
+        }
+
+        return indices;
+    }
+}
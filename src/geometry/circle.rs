@@ -1,4 +1,3 @@
-use super::triangle::Triangle;
 use super::Position;
 
 #[derive(Clone, Copy, Debug)]
@@ -8,42 +7,60 @@ pub struct Circle {
 }
 
 impl Circle {
+    // Angle of `target` seen from the center, in 0..2π, counter clockwise on screen (as if y pointed up)
     pub fn angle_to(&self, target: Position) -> f32 {
-        let triangle_to = Triangle {
-            p1: self.center,
-            p2: target,
-            p3: Position {
-                x: target.x,
-                y: self.center.y,
-            },
-        };
-
-        let a = triangle_to.p3.y - triangle_to.p2.y;
-        let b = triangle_to.p3.x - triangle_to.p1.x;
-
-        let mut angle = (a / b).atan() * (180.0 / 3.14);
-
-        if target.y > self.center.y && target.x > self.center.x {
-            angle = 360.0 + angle;
-        } else if target.x < self.center.x  {
-            angle = 180.0 + angle;
-        }
+        let offset = target - self.center;
+        let angle = Position::new(offset.x, -offset.y).angle();
 
-        return angle / (180.0 / 3.14);
+        return angle.rem_euclid(std::f32::consts::TAU);
     }
 
+    // Unit vector of an angle given by angle_to
     pub fn angle_to_coordinates(angle: f32) -> Position {
-        let x = angle.cos();
-        let y = -(angle.sin());
+        let direction = Position::from_angle(angle);
 
-        return Position {
-            x,
-            y,
-        };
+        return Position::new(direction.x, -direction.y);
     }
-    
+
     pub fn holds(&self, target: Position) -> bool {
         let distance = self.center.get_distance(target);
         return distance < self.radius as f32;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn angle_to_goes_counter_clockwise_on_screen() {
+        let circle = Circle {
+            center: Position::new(100.0, 100.0),
+            radius: 10,
+        };
+
+        assert_eq!(circle.angle_to(Position::new(150.0, 100.0)), 0.0);
+        // Above the center on screen
+        assert!((circle.angle_to(Position::new(100.0, 50.0)) - FRAC_PI_2).abs() < 1e-6);
+        assert!((circle.angle_to(Position::new(50.0, 100.0)) - PI).abs() < 1e-6);
+        // Straight below used to come out negative
+        assert!((circle.angle_to(Position::new(100.0, 150.0)) - 3.0 * FRAC_PI_2).abs() < 1e-6);
+        assert!((circle.angle_to(Position::new(150.0, 150.0)) - 7.0 * PI / 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn angle_to_coordinates_points_back_at_the_target() {
+        let circle = Circle {
+            center: Position::new(-20.0, 40.0),
+            radius: 10,
+        };
+
+        for target in [Position::new(0.0, 0.0), Position::new(-50.0, 90.0), Position::new(-20.0, 41.0)] {
+            let direction = Circle::angle_to_coordinates(circle.angle_to(target));
+            let expected = (target - circle.center).normalize();
+
+            assert!((direction - expected).length() < 1e-5);
+        }
+    }
+}
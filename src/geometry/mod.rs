@@ -1,32 +1,225 @@
 pub mod rectangle;
-pub mod triangle;
 pub mod circle;
+pub mod shape;
 
-use triangle::Triangle;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-#[derive(Copy, Clone, Debug)]
+// A point on the map or the screen, and just as well a 2D vector between two of them (velocities,
+// steering directions, offsets). The y axis points down, so positive angles turn clockwise on screen.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
 }
 
 impl Position {
-    pub fn get_triangle_to(&self, target: Position) -> Triangle {
-        Triangle {
-            p1: *self,
-            p2: target,
-            p3: Position {
-                x: target.x,
-                y: self.y,
-            },
-        }
+    pub const ZERO: Position = Position { x: 0.0, y: 0.0 };
+
+    pub fn new(x: f32, y: f32) -> Position {
+        return Position { x, y };
     }
+
+    // Unit vector pointing at `angle` radians from the x axis
+    pub fn from_angle(angle: f32) -> Position {
+        let (sin, cos) = angle.sin_cos();
+        return Position { x: cos, y: sin };
+    }
+
     pub fn get_distance(&self, target: Position) -> f32 {
-        let triangle = self.get_triangle_to(target);
+        return (target - *self).length();
+    }
+
+    pub fn dot(self, other: Position) -> f32 {
+        return self.x * other.x + self.y * other.y;
+    }
+
+    // Cheaper than length when only comparing
+    pub fn length_squared(self) -> f32 {
+        return self.dot(self);
+    }
+
+    pub fn length(self) -> f32 {
+        return self.x.hypot(self.y);
+    }
+
+    // Same direction with a length of 1, the zero vector stays zero
+    pub fn normalize(self) -> Position {
+        let length = self.length();
+        if length == 0.0 {
+            return Position::ZERO;
+        }
+
+        return self / length;
+    }
+
+    // `alpha` 0 is self, 1 is `to`, anything in between blends the two
+    pub fn lerp(self, to: Position, alpha: f32) -> Position {
+        return self + (to - self) * alpha;
+    }
+
+    // Angle from the x axis in radians, in -π..=π
+    pub fn angle(self) -> f32 {
+        return self.y.atan2(self.x);
+    }
 
-        Triangle::get_hypotenuses_size(
-            (triangle.p1.x - triangle.p3.x).abs(),
-            (triangle.p3.y - triangle.p2.y).abs(),
-        )
+    // Turned by `angle` radians around the origin
+    pub fn rotate(self, angle: f32) -> Position {
+        let (sin, cos) = angle.sin_cos();
+        return Position {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        };
     }
-}
\ No newline at end of file
+}
+
+impl Add for Position {
+    type Output = Position;
+
+    fn add(self, other: Position) -> Position {
+        return Position::new(self.x + other.x, self.y + other.y);
+    }
+}
+
+impl Sub for Position {
+    type Output = Position;
+
+    fn sub(self, other: Position) -> Position {
+        return Position::new(self.x - other.x, self.y - other.y);
+    }
+}
+
+impl Mul<f32> for Position {
+    type Output = Position;
+
+    fn mul(self, scale: f32) -> Position {
+        return Position::new(self.x * scale, self.y * scale);
+    }
+}
+
+impl Div<f32> for Position {
+    type Output = Position;
+
+    fn div(self, scale: f32) -> Position {
+        return Position::new(self.x / scale, self.y / scale);
+    }
+}
+
+impl Neg for Position {
+    type Output = Position;
+
+    fn neg(self) -> Position {
+        return Position::new(-self.x, -self.y);
+    }
+}
+
+impl AddAssign for Position {
+    fn add_assign(&mut self, other: Position) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Position {
+    fn sub_assign(&mut self, other: Position) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Position {
+    fn mul_assign(&mut self, scale: f32) {
+        *self = *self * scale;
+    }
+}
+
+impl DivAssign<f32> for Position {
+    fn div_assign(&mut self, scale: f32) {
+        *self = *self / scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn close(a: Position, b: Position) -> bool {
+        return (a - b).length() < 1e-4;
+    }
+
+    #[test]
+    fn operators() {
+        let a = Position::new(3.0, -4.0);
+        let b = Position::new(1.0, 2.0);
+
+        assert_eq!(a + b, Position::new(4.0, -2.0));
+        assert_eq!(a - b, Position::new(2.0, -6.0));
+        assert_eq!(a * 2.0, Position::new(6.0, -8.0));
+        assert_eq!(a / 2.0, Position::new(1.5, -2.0));
+        assert_eq!(-a, Position::new(-3.0, 4.0));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        c *= 3.0;
+        c /= 2.0;
+        assert_eq!(c, Position::new(1.5, 3.0));
+    }
+
+    #[test]
+    fn lengths_and_distances() {
+        let a = Position::new(3.0, -4.0);
+
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(a.length_squared(), 25.0);
+        assert_eq!(a.dot(Position::new(2.0, 1.0)), 2.0);
+        assert_eq!(a.get_distance(Position::ZERO), 5.0);
+        assert_eq!(Position::new(1.0, 1.0).get_distance(Position::new(4.0, 5.0)), 5.0);
+        // Perpendicular vectors
+        assert_eq!(a.dot(Position::new(4.0, 3.0)), 0.0);
+    }
+
+    #[test]
+    fn normalize() {
+        assert!(close(Position::new(3.0, -4.0).normalize(), Position::new(0.6, -0.8)));
+        assert!((Position::new(-120.0, 7.5).normalize().length() - 1.0).abs() < 1e-6);
+        assert_eq!(Position::ZERO.normalize(), Position::ZERO);
+    }
+
+    #[test]
+    fn lerp() {
+        let from = Position::new(0.0, 10.0);
+        let to = Position::new(10.0, -10.0);
+
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+        assert_eq!(from.lerp(to, 0.25), Position::new(2.5, 5.0));
+        // Past the end it keeps going
+        assert_eq!(from.lerp(to, 2.0), Position::new(20.0, -30.0));
+    }
+
+    #[test]
+    fn angles() {
+        assert_eq!(Position::new(1.0, 0.0).angle(), 0.0);
+        assert_eq!(Position::new(0.0, 2.0).angle(), FRAC_PI_2);
+        assert_eq!(Position::new(-1.0, 0.0).angle(), PI);
+        assert_eq!(Position::new(0.0, -1.0).angle(), -FRAC_PI_2);
+
+        assert!(close(Position::from_angle(0.0), Position::new(1.0, 0.0)));
+        assert!(close(Position::from_angle(FRAC_PI_2), Position::new(0.0, 1.0)));
+
+        // from_angle and angle undo each other
+        for step in -15..=15 {
+            let angle = step as f32 * 0.2;
+            assert!((Position::from_angle(angle).angle() - angle).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn rotate() {
+        let a = Position::new(2.0, 0.0);
+
+        assert!(close(a.rotate(FRAC_PI_2), Position::new(0.0, 2.0)));
+        assert!(close(a.rotate(PI), Position::new(-2.0, 0.0)));
+        assert!(close(a.rotate(0.3).rotate(-0.3), a));
+        assert!((Position::new(3.0, 4.0).rotate(1.234).length() - 5.0).abs() < 1e-5);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Position};
+use super::{Position, shape::Shape};
 
 pub struct RectangleCornersPositions {
     pub top_left: Position,
@@ -67,29 +67,9 @@ impl Rectangle {
         return corners;
     }
 
-    pub fn contains_position(&self, position: Position) -> bool {
-        let corners = self.get_corners();
-
-        if position.x > corners.top_left.x
-        && (position.x as i64) < corners.top_right.x as i64
-        && position.y > corners.top_left.y
-        && (position.y as i64) < corners.bottom_left.y as i64 {
-            return true;
-        }
-        return false;
-    }
-
+    // Whether the two overlap at all, even when no corner of either is inside the other
     pub fn over(&self, outside_rectangle: Rectangle) -> bool {
-        let corners = self.get_corners();
-
-        if outside_rectangle.contains_position(corners.top_left)
-        || outside_rectangle.contains_position(corners.top_right)
-        || outside_rectangle.contains_position(corners.bottom_left)
-        || outside_rectangle.contains_position(corners.bottom_right) {
-            return true;
-        }
-
-        return false;
+        return self.intersects(&outside_rectangle);
     }
 
     pub fn closest_position_within(&self, position: Position) -> Position {
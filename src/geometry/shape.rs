@@ -0,0 +1,475 @@
+use std::f64::consts::PI;
+
+use super::{Position, circle::Circle, rectangle::Rectangle};
+
+// A borrowed circle or rectangle, so two shapes can be compared whatever their types
+pub enum ShapeRef<'a> {
+    Circle(&'a Circle),
+    Rectangle(&'a Rectangle),
+}
+
+// What the culling and the collisions need to know about how shapes sit relative to each other.
+// Touching edges don't intersect, same as Circle::holds doesn't hold the points on its edge,
+// but a shape touching the inside of another's edge is still contained in it.
+pub trait Shape {
+    fn as_shape(&self) -> ShapeRef<'_>;
+
+    fn area(&self) -> f32;
+
+    fn contains_position(&self, position: Position) -> bool;
+
+    fn intersects(&self, other: &impl Shape) -> bool {
+        return match (self.as_shape(), other.as_shape()) {
+            (ShapeRef::Circle(a), ShapeRef::Circle(b)) => {
+                let reach = a.radius as f32 + b.radius as f32;
+                (b.center - a.center).length_squared() < reach * reach
+            }
+            (ShapeRef::Rectangle(a), ShapeRef::Rectangle(b)) => {
+                let (a, b) = (Bounds::of(a), Bounds::of(b));
+                a.left < b.right && b.left < a.right && a.top < b.bottom && b.top < a.bottom
+            }
+            (ShapeRef::Circle(circle), ShapeRef::Rectangle(rectangle))
+            | (ShapeRef::Rectangle(rectangle), ShapeRef::Circle(circle)) => {
+                let radius = circle.radius as f32;
+                let offset = circle.center - Bounds::of(rectangle).clamp(circle.center);
+                offset.length_squared() < radius * radius
+            }
+        };
+    }
+
+    // Whether `other` lies entirely within this shape
+    fn contains(&self, other: &impl Shape) -> bool {
+        return match (self.as_shape(), other.as_shape()) {
+            (ShapeRef::Circle(outer), ShapeRef::Circle(inner)) => {
+                outer.center.get_distance(inner.center) + inner.radius as f32 <= outer.radius as f32
+            }
+            (ShapeRef::Rectangle(outer), ShapeRef::Rectangle(inner)) => {
+                let (outer, inner) = (Bounds::of(outer), Bounds::of(inner));
+                outer.left <= inner.left && inner.right <= outer.right && outer.top <= inner.top && inner.bottom <= outer.bottom
+            }
+            (ShapeRef::Rectangle(outer), ShapeRef::Circle(inner)) => {
+                let radius = inner.radius as f32;
+                let outer = Bounds::of(outer);
+                outer.left <= inner.center.x - radius
+                    && inner.center.x + radius <= outer.right
+                    && outer.top <= inner.center.y - radius
+                    && inner.center.y + radius <= outer.bottom
+            }
+            // Every corner has to be in the circle
+            (ShapeRef::Circle(outer), ShapeRef::Rectangle(inner)) => {
+                let radius = outer.radius as f32;
+                let corners = inner.get_corners();
+                [corners.top_left, corners.top_right, corners.bottom_left, corners.bottom_right]
+                    .iter()
+                    .all(|corner| (*corner - outer.center).length_squared() <= radius * radius)
+            }
+        };
+    }
+
+    // Area covered by both shapes
+    fn overlap_area(&self, other: &impl Shape) -> f32 {
+        if !self.intersects(other) {
+            return 0.0;
+        }
+
+        return match (self.as_shape(), other.as_shape()) {
+            (ShapeRef::Circle(a), ShapeRef::Circle(b)) => circles_overlap_area(a, b),
+            (ShapeRef::Rectangle(a), ShapeRef::Rectangle(b)) => {
+                let (a, b) = (Bounds::of(a), Bounds::of(b));
+                let width = (a.right.min(b.right) - a.left.max(b.left)).max(0.0);
+                let height = (a.bottom.min(b.bottom) - a.top.max(b.top)).max(0.0);
+                width * height
+            }
+            (ShapeRef::Circle(circle), ShapeRef::Rectangle(rectangle))
+            | (ShapeRef::Rectangle(rectangle), ShapeRef::Circle(circle)) => circle_rectangle_overlap_area(circle, rectangle),
+        };
+    }
+
+    // How far one of the shapes has to move for them to stop intersecting, 0 if they don't
+    fn penetration_depth(&self, other: &impl Shape) -> f32 {
+        if !self.intersects(other) {
+            return 0.0;
+        }
+
+        return match (self.as_shape(), other.as_shape()) {
+            (ShapeRef::Circle(a), ShapeRef::Circle(b)) => {
+                (a.radius as f32 + b.radius as f32 - a.center.get_distance(b.center)).max(0.0)
+            }
+            (ShapeRef::Rectangle(a), ShapeRef::Rectangle(b)) => {
+                let (a, b) = (Bounds::of(a), Bounds::of(b));
+                let width = a.right.min(b.right) - a.left.max(b.left);
+                let height = a.bottom.min(b.bottom) - a.top.max(b.top);
+                width.min(height).max(0.0)
+            }
+            (ShapeRef::Circle(circle), ShapeRef::Rectangle(rectangle))
+            | (ShapeRef::Rectangle(rectangle), ShapeRef::Circle(circle)) => {
+                let radius = circle.radius as f32;
+                let bounds = Bounds::of(rectangle);
+                let center = circle.center;
+
+                match bounds.holds(center) {
+                    // Out through the closest edge
+                    true => radius + (center.x - bounds.left).min(bounds.right - center.x).min(center.y - bounds.top).min(bounds.bottom - center.y),
+                    false => (radius - center.get_distance(bounds.clamp(center))).max(0.0),
+                }
+            }
+        };
+    }
+}
+
+impl Shape for Circle {
+    fn as_shape(&self) -> ShapeRef<'_> {
+        return ShapeRef::Circle(self);
+    }
+
+    fn area(&self) -> f32 {
+        return (PI * (self.radius as f64).powi(2)) as f32;
+    }
+
+    fn contains_position(&self, position: Position) -> bool {
+        return self.holds(position);
+    }
+}
+
+impl Shape for Rectangle {
+    fn as_shape(&self) -> ShapeRef<'_> {
+        return ShapeRef::Rectangle(self);
+    }
+
+    fn area(&self) -> f32 {
+        let size = Rectangle::to_rectangle_size(self.size.clone());
+        return size.width as f32 * size.height as f32;
+    }
+
+    fn contains_position(&self, position: Position) -> bool {
+        let bounds = Bounds::of(self);
+        return bounds.left < position.x && position.x < bounds.right && bounds.top < position.y && position.y < bounds.bottom;
+    }
+}
+
+// So a shape of either type can be passed along as one
+impl Shape for ShapeRef<'_> {
+    fn as_shape(&self) -> ShapeRef<'_> {
+        return match self {
+            ShapeRef::Circle(circle) => ShapeRef::Circle(circle),
+            ShapeRef::Rectangle(rectangle) => ShapeRef::Rectangle(rectangle),
+        };
+    }
+
+    fn area(&self) -> f32 {
+        return match self {
+            ShapeRef::Circle(circle) => circle.area(),
+            ShapeRef::Rectangle(rectangle) => rectangle.area(),
+        };
+    }
+
+    fn contains_position(&self, position: Position) -> bool {
+        return match self {
+            ShapeRef::Circle(circle) => circle.contains_position(position),
+            ShapeRef::Rectangle(rectangle) => rectangle.contains_position(position),
+        };
+    }
+}
+
+// Edges of a rectangle
+struct Bounds {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl Bounds {
+    fn of(rectangle: &Rectangle) -> Bounds {
+        let corners = rectangle.get_corners();
+        return Bounds {
+            left: corners.top_left.x,
+            top: corners.top_left.y,
+            right: corners.bottom_right.x,
+            bottom: corners.bottom_right.y,
+        };
+    }
+
+    // Edges included
+    fn holds(&self, position: Position) -> bool {
+        return self.left <= position.x && position.x <= self.right && self.top <= position.y && position.y <= self.bottom;
+    }
+
+    // The point of the rectangle closest to `position`, `position` itself when it's inside
+    fn clamp(&self, position: Position) -> Position {
+        return Position::new(position.x.clamp(self.left, self.right), position.y.clamp(self.top, self.bottom));
+    }
+}
+
+// The lens where the two circles cross, worked out in f64 since it subtracts areas of similar size
+fn circles_overlap_area(a: &Circle, b: &Circle) -> f32 {
+    let (ra, rb) = (a.radius as f64, b.radius as f64);
+    let distance = a.center.get_distance(b.center) as f64;
+
+    if distance >= ra + rb {
+        return 0.0;
+    }
+    if distance <= (ra - rb).abs() {
+        return (PI * ra.min(rb).powi(2)) as f32;
+    }
+
+    let alpha = ((distance * distance + ra * ra - rb * rb) / (2.0 * distance * ra)).clamp(-1.0, 1.0).acos();
+    let beta = ((distance * distance + rb * rb - ra * ra) / (2.0 * distance * rb)).clamp(-1.0, 1.0).acos();
+    let kite = ((-distance + ra + rb) * (distance + ra - rb) * (distance - ra + rb) * (distance + ra + rb)).max(0.0).sqrt();
+
+    return (ra * ra * alpha + rb * rb * beta - kite / 2.0) as f32;
+}
+
+// Inclusion-exclusion over the areas of the circle below and left of each corner
+fn circle_rectangle_overlap_area(circle: &Circle, rectangle: &Rectangle) -> f32 {
+    let bounds = Bounds::of(rectangle);
+    let radius = circle.radius as f64;
+    let (left, right) = ((bounds.left - circle.center.x) as f64, (bounds.right - circle.center.x) as f64);
+    let (top, bottom) = ((bounds.top - circle.center.y) as f64, (bounds.bottom - circle.center.y) as f64);
+
+    let area = corner_area(right, bottom, radius) - corner_area(left, bottom, radius) - corner_area(right, top, radius) + corner_area(left, top, radius);
+
+    return area.max(0.0) as f32;
+}
+
+// Area of the circle of `radius` around the origin with X <= x and Y <= y.
+// Integrated column by column: where the circle is taller than |y| a column spans -h..y,
+// elsewhere it spans the whole column when y is above the center and nothing when below.
+fn corner_area(x: f64, y: f64, radius: f64) -> f64 {
+    let x = x.min(radius);
+    if x <= -radius || y <= -radius {
+        return 0.0;
+    }
+    let y = y.min(radius);
+
+    // Area under the half circle height h(X) = √(radius² - X²) from -radius to `to`
+    let half = |to: f64| {
+        let to = to.clamp(-radius, radius);
+        let height = (radius * radius - to * to).max(0.0).sqrt();
+        return (to * height + radius * radius * (to / radius).asin()) / 2.0 + PI * radius * radius / 4.0;
+    };
+
+    let span = (radius * radius - y * y).max(0.0).sqrt();
+
+    let mut area = 0.0;
+    if x > -span {
+        let to = x.min(span);
+        area += y * (to + span) + half(to) - half(-span);
+    }
+    if y > 0.0 {
+        area += 2.0 * half(x.min(-span));
+        if x > span {
+            area += 2.0 * (half(x) - half(span));
+        }
+    }
+
+    return area;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::rectangle::{RectangleSize, Size};
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    fn circle(x: f32, y: f32, radius: u32) -> Circle {
+        return Circle {
+            center: Position::new(x, y),
+            radius,
+        };
+    }
+
+    fn rectangle(x: f32, y: f32, width: u32, height: u32) -> Rectangle {
+        return Rectangle {
+            position: Position::new(x, y),
+            size: Size::Rectangle(RectangleSize { width, height }),
+        };
+    }
+
+    fn random_circle(rng: &mut StdRng) -> Circle {
+        return circle(rng.gen_range(-150.0..150.0), rng.gen_range(-150.0..150.0), rng.gen_range(1..100));
+    }
+
+    fn random_rectangle(rng: &mut StdRng) -> Rectangle {
+        return rectangle(rng.gen_range(-150.0..150.0), rng.gen_range(-150.0..150.0), rng.gen_range(1..200), rng.gen_range(1..200));
+    }
+
+    // Runs `check` on every kind of pair, in both orders
+    fn for_random_pairs(seed: u64, pairs: usize, check: impl Fn(&ShapeRef, &ShapeRef)) {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        for _ in 0..pairs {
+            let (a, b) = (random_circle(&mut rng), random_circle(&mut rng));
+            let (c, d) = (random_rectangle(&mut rng), random_rectangle(&mut rng));
+
+            for (first, second) in [
+                (a.as_shape(), b.as_shape()),
+                (c.as_shape(), d.as_shape()),
+                (a.as_shape(), c.as_shape()),
+                (c.as_shape(), a.as_shape()),
+            ] {
+                check(&first, &second);
+            }
+        }
+    }
+
+    fn close(a: f32, b: f32, tolerance: f32) -> bool {
+        return (a - b).abs() <= tolerance;
+    }
+
+    #[test]
+    fn symmetric() {
+        for_random_pairs(1, 2000, |a, b| {
+            assert_eq!(a.intersects(b), b.intersects(a));
+            assert!(close(a.overlap_area(b), b.overlap_area(a), 1e-2));
+            assert_eq!(a.penetration_depth(b), b.penetration_depth(a));
+        });
+    }
+
+    #[test]
+    fn overlap_only_when_intersecting() {
+        for_random_pairs(2, 2000, |a, b| {
+            let area = a.overlap_area(b);
+            let depth = a.penetration_depth(b);
+
+            match a.intersects(b) {
+                true => assert!(depth > 0.0),
+                false => assert!(area == 0.0 && depth == 0.0),
+            }
+            assert!(area <= a.area().min(b.area()) * 1.0001 + 1e-2);
+        });
+    }
+
+    #[test]
+    fn contained_shapes_overlap_entirely() {
+        let mut contained = 0;
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..4000 {
+            let (a, b) = (random_circle(&mut rng), random_rectangle(&mut rng));
+            // Small ones so some end up inside the others
+            let (c, d) = (circle(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0), rng.gen_range(1..20)), rectangle(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0), rng.gen_range(1..40), rng.gen_range(1..40)));
+
+            for (outer, inner) in [
+                (a.as_shape(), c.as_shape()),
+                (a.as_shape(), d.as_shape()),
+                (b.as_shape(), c.as_shape()),
+                (b.as_shape(), d.as_shape()),
+            ] {
+                if !outer.contains(&inner) {
+                    continue;
+                }
+
+                contained += 1;
+                assert!(outer.intersects(&inner));
+                assert!(!inner.contains(&outer) || close(outer.area(), inner.area(), 1e-2));
+                assert!(close(outer.overlap_area(&inner), inner.area(), inner.area() * 1e-3 + 1e-2));
+                // Every sampled point of the inner shape is in the outer one
+                for _ in 0..20 {
+                    let position = Position::new(rng.gen_range(-200.0..200.0), rng.gen_range(-200.0..200.0));
+                    assert!(!inner.contains_position(position) || outer.contains_position(position));
+                }
+            }
+        }
+
+        assert!(contained > 100);
+    }
+
+    // Counts the sampled points of the area both shapes could cover
+    #[test]
+    fn overlap_area_matches_sampling() {
+        const SAMPLES: usize = 200;
+
+        for_random_pairs(4, 60, |a, b| {
+            let (mut left, mut right, mut top, mut bottom) = (-300.0f32, 300.0f32, -300.0f32, 300.0f32);
+            for shape in [a, b] {
+                let (x, y, half_width, half_height) = match shape {
+                    ShapeRef::Circle(circle) => (circle.center.x, circle.center.y, circle.radius as f32, circle.radius as f32),
+                    ShapeRef::Rectangle(rectangle) => {
+                        let size = Rectangle::to_rectangle_size(rectangle.size.clone());
+                        (rectangle.position.x, rectangle.position.y, size.width as f32 / 2.0, size.height as f32 / 2.0)
+                    }
+                };
+                left = left.max(x - half_width);
+                right = right.min(x + half_width);
+                top = top.max(y - half_height);
+                bottom = bottom.min(y + half_height);
+            }
+            if left >= right || top >= bottom {
+                assert_eq!(a.overlap_area(b), 0.0);
+                return;
+            }
+
+            let (width, height) = ((right - left) / SAMPLES as f32, (bottom - top) / SAMPLES as f32);
+            let mut inside = 0;
+            for column in 0..SAMPLES {
+                for row in 0..SAMPLES {
+                    let position = Position::new(left + (column as f32 + 0.5) * width, top + (row as f32 + 0.5) * height);
+                    if a.contains_position(position) && b.contains_position(position) {
+                        inside += 1;
+                    }
+                }
+            }
+
+            let sampled = inside as f32 * width * height;
+            let box_area = (right - left) * (bottom - top);
+            assert!(close(a.overlap_area(b), sampled, box_area * 0.01 + 1.0), "{} vs sampled {}", a.overlap_area(b), sampled);
+        });
+    }
+
+    #[test]
+    fn separating_by_the_penetration_depth() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..2000 {
+            let (a, b) = (random_circle(&mut rng), random_circle(&mut rng));
+            let depth = a.penetration_depth(&b);
+            if depth == 0.0 || a.center == b.center {
+                continue;
+            }
+
+            let away = (b.center - a.center).normalize();
+            let nudged = |distance: f32| Circle {
+                center: b.center + away * distance,
+                ..b
+            };
+            assert!(!a.intersects(&nudged(depth + 1e-2)));
+            assert!(a.intersects(&nudged(depth * 0.9)));
+        }
+    }
+
+    #[test]
+    fn known_cases() {
+        let square = rectangle(0.0, 0.0, 100, 100);
+
+        // Crossing without any corner of one inside the other
+        let cross = rectangle(0.0, 0.0, 20, 300);
+        assert!(square.intersects(&cross) && square.over(cross.clone()));
+        assert_eq!(square.overlap_area(&cross), 2000.0);
+        assert_eq!(square.penetration_depth(&cross), 20.0);
+
+        // Sharing an edge only
+        let beside = rectangle(100.0, 0.0, 100, 100);
+        assert!(!square.intersects(&beside) && !square.over(beside));
+
+        // Near a corner, inside the bounding box of the circle but out of reach
+        let corner = circle(60.0, 60.0, 14);
+        assert!(!square.intersects(&corner));
+        assert!(square.intersects(&circle(60.0, 60.0, 15)));
+
+        // Half of a circle on an edge
+        let half = circle(50.0, 0.0, 10);
+        assert!(close(square.overlap_area(&half), half.area() / 2.0, 1e-2));
+        assert_eq!(square.penetration_depth(&half), 10.0);
+        // Centered in it, it has to go out through the closest edge
+        assert_eq!(square.penetration_depth(&circle(40.0, 0.0, 5)), 15.0);
+
+        assert!(square.contains(&circle(0.0, 0.0, 50)));
+        assert!(!square.contains(&circle(0.0, 0.0, 51)));
+        assert!(circle(0.0, 0.0, 71).contains(&square));
+        assert!(!circle(0.0, 0.0, 70).contains(&square));
+
+        assert!(close(circle(0.0, 0.0, 10).overlap_area(&circle(10.0, 0.0, 10)), 200.0 * (std::f32::consts::PI / 3.0) - 50.0 * 3.0f32.sqrt(), 1e-2));
+    }
+}
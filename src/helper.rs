@@ -1,17 +1,38 @@
-use crate::game::{player::Player, Game};
+use crate::game::{Game, player::JoinRequest, chat::ChatMessage, console::Issuer, ids::PlayerId};
 
 use crate::geometry::Position;
 
 pub enum G2UMessage {
-    StateUpdate(Game)
+    // Boxed so the other messages don't take the size of a whole game
+    StateUpdate(Box<Game>),
+    // The ID the game gave to the player who just joined
+    Joined(PlayerId),
+    // Text answer to a console command, for the issuer
+    CommandOutput(Issuer, String),
+    // Broadcast to everyone
+    Chat(ChatMessage),
+    // Why a chat message of the player wasn't sent, only for them
+    ChatNotice(PlayerId, String),
 }
 
 pub enum PlayerEvent {
+    // Target relative to the player's first body part, its length sets the speed
     Moving(Position),
+    Split,
+    Eject,
 }
 
 pub enum U2GMessage {
-    PlayerEvent(String, PlayerEvent),
-    NewPlayer(Player),
+    PlayerEvent(PlayerId, PlayerEvent),
+    // Answered with G2UMessage::Joined
+    Join(JoinRequest),
+    Pause(PlayerId),
+    Resume(PlayerId),
+    Chat(PlayerId, String),
+    // Console command typed by a player, see game::console.
+    // The terminal of a headless game has its own channel, see Game::init_with_console
+    Command(PlayerId, String),
+    // Runs a single tick while the simulation is paused
+    Step,
     Quit,
 }
\ No newline at end of file
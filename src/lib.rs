@@ -0,0 +1,13 @@
+// The game simulation, without any window: the client, the headless server
+// and the tools all drive a game::Game through the channels in helper
+
+extern crate rand;
+
+pub mod time;
+pub mod settings;
+pub mod config;
+pub mod error;
+pub mod logging;
+pub mod helper;
+pub mod geometry;
+pub mod game;
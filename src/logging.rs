@@ -0,0 +1,294 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::time;
+use crate::config::Config;
+
+// Structured logs: every record has a level, a message, the spans it happened in and
+// a list of `key=value` fields. Written either as text lines or as JSON lines,
+// to stderr or appended to a file, as set in the `[log]` section of the config.
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+struct Logger {
+    level: Level,
+    format: Format,
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+thread_local! {
+    // Names of the spans the current thread is in, outermost first
+    static SPANS: RefCell<Vec<&'static str>> = const { RefCell::new(vec![]) };
+}
+
+impl Level {
+    pub fn parse(level: &str) -> Option<Level> {
+        match level.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+// Sets the logger up from the config, only the first call has any effect.
+// Nothing is logged before it's called.
+pub fn init(config: &Config) {
+    let level = config
+        .get("log.level")
+        .and_then(Level::parse)
+        .unwrap_or(Level::Info);
+
+    let format = match config.get("log.format") {
+        Some("json") => Format::Json,
+        _ => Format::Text,
+    };
+
+    let mut file_error = None;
+    let output: Box<dyn Write + Send> = match config.get("log.output") {
+        None | Some("stderr") => Box::new(io::stderr()),
+        Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Box::new(file),
+            Err(error) => {
+                file_error = Some((path.to_string(), error.to_string()));
+                Box::new(io::stderr())
+            }
+        },
+    };
+
+    let _ = LOGGER.set(Logger {
+        level,
+        format,
+        output: Mutex::new(output),
+    });
+
+    if let Some((path, error)) = file_error {
+        log(Level::Warn, "couldn't open the log file, logging to stderr", &[("path", &path), ("error", &error)]);
+    }
+}
+
+pub fn enabled(level: Level) -> bool {
+    match LOGGER.get() {
+        Some(logger) => level <= logger.level,
+        None => false,
+    }
+}
+
+pub fn log(level: Level, message: &str, fields: &[(&str, &dyn Display)]) {
+    let logger = match LOGGER.get() {
+        Some(logger) if level <= logger.level => logger,
+        _ => return,
+    };
+
+    let spans = SPANS.with(|spans| spans.borrow().join(">"));
+    let timestamp = format_timestamp(time::now());
+
+    let line = match logger.format {
+        Format::Text => {
+            let mut line = format!("{} {:5}", timestamp, level);
+            if !spans.is_empty() {
+                line += &format!(" {}", spans);
+            }
+            line += &format!(" {}", message);
+            for (key, value) in fields {
+                line += &format!(" {}={}", key, quote_if_needed(&value.to_string()));
+            }
+            line
+        }
+        Format::Json => {
+            let mut line = format!(
+                "{{\"timestamp\":{},\"level\":{},\"message\":{}",
+                json_string(&timestamp),
+                json_string(&level.to_string()),
+                json_string(message),
+            );
+            if !spans.is_empty() {
+                line += &format!(",\"span\":{}", json_string(&spans));
+            }
+            for (key, value) in fields {
+                line += &format!(",{}:{}", json_string(key), json_string(&value.to_string()));
+            }
+            line + "}"
+        }
+    };
+
+    // A poisoned lock only means another thread panicked while writing, the output is still usable
+    let mut output = match logger.output.lock() {
+        Ok(output) => output,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let _ = writeln!(output, "{}", line);
+}
+
+// Marks a stretch of work, every record logged on this thread while it's alive is tagged
+// with its name. Logs how long it took, at its own level, once dropped.
+pub struct Span {
+    name: &'static str,
+    level: Level,
+    start: Option<Instant>,
+}
+
+pub fn span(level: Level, name: &'static str) -> Span {
+    // Disabled spans cost next to nothing, the tick span is created a few hundred times a second
+    if !enabled(level) {
+        return Span {
+            name,
+            level,
+            start: None,
+        };
+    }
+
+    SPANS.with(|spans| spans.borrow_mut().push(name));
+
+    return Span {
+        name,
+        level,
+        start: Some(Instant::now()),
+    };
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let start = match self.start {
+            Some(start) => start,
+            None => return,
+        };
+
+        let elapsed_us = start.elapsed().as_micros();
+        log(self.level, "span closed", &[("span_name", &self.name), ("elapsed_us", &elapsed_us)]);
+
+        SPANS.with(|spans| spans.borrow_mut().pop());
+    }
+}
+
+// Values with spaces or quotes are quoted so text lines stay easy to split
+fn quote_if_needed(value: &str) -> String {
+    if value.is_empty() || value.contains(|character: char| character.is_whitespace() || character == '"' || character == '=') {
+        return format!("{:?}", value);
+    }
+
+    return value.to_string();
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32));
+            }
+            character => escaped.push(character),
+        }
+    }
+
+    escaped.push('"');
+    return escaped;
+}
+
+// RFC 3339 in UTC with milliseconds, e.g. 2024-01-31T12:00:00.000Z
+fn format_timestamp(nanoseconds: u128) -> String {
+    let milliseconds = (nanoseconds / 1_000_000) as u64;
+    let seconds = milliseconds / 1000;
+    let days = (seconds / 86_400) as i64;
+    let seconds_of_day = seconds % 86_400;
+
+    // Days since 1970-01-01 to a civil date, from Howard Hinnant's date algorithms
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        milliseconds % 1000,
+    );
+}
+
+// `info!("player joined", player_id = player.id, name = player.name)`,
+// field values only need to implement Display
+#[macro_export]
+macro_rules! log_event {
+    ($level:expr, $message:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::logging::enabled($level) {
+            $crate::logging::log($level, $message, &[$((stringify!($key), &$value as &dyn std::fmt::Display)),*]);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arguments:tt)*) => { $crate::log_event!($crate::logging::Level::Error, $($arguments)*) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arguments:tt)*) => { $crate::log_event!($crate::logging::Level::Warn, $($arguments)*) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arguments:tt)*) => { $crate::log_event!($crate::logging::Level::Info, $($arguments)*) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arguments:tt)*) => { $crate::log_event!($crate::logging::Level::Debug, $($arguments)*) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arguments:tt)*) => { $crate::log_event!($crate::logging::Level::Trace, $($arguments)*) };
+}
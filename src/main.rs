@@ -1,34 +1,63 @@
 extern crate sdl2;
-extern crate rand;
-extern crate uuid;
-
-pub mod time;
-pub mod settings;
-pub mod helper;
-pub mod geometry;
-pub mod game;
+extern crate agario;
+
 pub mod ui;
 
 use std::sync::mpsc;
 
-use ui::UiSettings;
-use game::Game;
+use agario::{config::Config, logging, info, error};
+use agario::game::{Game, Mode};
+use agario::helper::{G2UMessage, U2GMessage};
+use agario::error::{GameError, TransportError};
+use ui::{UiSettings, input::Bindings, settings};
 
 fn main() {
-    let (g2u_tx, g2u_rx) = mpsc::channel::<helper::G2UMessage>();
-    let (u2g_tx, u2g_rx) = mpsc::channel::<helper::U2GMessage>();
+    let (g2u_tx, g2u_rx) = mpsc::channel::<G2UMessage>();
+    let (u2g_tx, u2g_rx) = mpsc::channel::<U2GMessage>();
+
+    let config = Config::load(settings::CONFIG_FILE_PATH);
+
+    logging::init(&config);
+    info!("starting", binary = "client", config = settings::CONFIG_FILE_PATH);
 
-    std::thread::spawn(move || {
-        let mut window = ui::Ui::new("Arthur", UiSettings {
+    let mut game = Game::new(Mode::Local);
+    game.apply_config(&config);
+    // Drives the kill feed and the effects
+    let events_rx = game.subscribe();
+
+    let ui_thread = std::thread::spawn(move || {
+        let mut window = ui::Ui::new(UiSettings {
             title: String::from(settings::WINDOW_TITLE),
             width: settings::WINDOW_WIDTH,
             height: settings::WINDOW_HEIGHT,
-        });
+            bindings: Bindings::from_config(&config),
+        })?;
 
-        window.run(&u2g_tx, &g2u_rx);
+        window.run(&u2g_tx, &g2u_rx, &events_rx)
     });
 
-    let mut game = Game::new();
-
-    game.init(&g2u_tx, &u2g_rx);
-}
\ No newline at end of file
+    let game_result = game.init(&g2u_tx, &u2g_rx);
+    // Lets the UI see the game is gone
+    drop(g2u_tx);
+
+    match ui_thread.join() {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => {
+            error!("ui failed", error = error);
+            std::process::exit(1);
+        }
+        Err(_) => {
+            error!("ui thread panicked");
+            std::process::exit(1);
+        }
+    }
+
+    match game_result {
+        // The window closing right after the player quit is the normal way out
+        Ok(()) | Err(GameError::Transport(TransportError::UiDisconnected)) => {}
+        Err(error) => {
+            error!("game failed", error = error);
+            std::process::exit(1);
+        }
+    }
+}
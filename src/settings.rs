@@ -1,36 +1,69 @@
-use sdl2::pixels::Color;
+use crate::game::player::PlayerColor;
 
-use crate::ui::DebugOptions;
-
-// Window
-pub const WINDOW_TITLE: &str = "Agar.io";
-pub const WINDOW_WIDTH: u32 = 600;
-pub const WINDOW_HEIGHT: u32 = 600;
+// Simulation
 pub const MAX_FPS: u16 = 300;
+// Highest tick rate the config or the console may set
+pub const MAX_TICK_RATE: u16 = 1000;
+// Work below this many entities per thread is done on fewer threads, spawning them would cost more.
+// A guess for now, like the rest of the parallel systems it hasn't been measured on several cores
+pub const MIN_ITEMS_PER_THREAD: usize = 1024;
 
-// Sprites
-pub const SPRITES_FOLDER_PATH: &str = "./assets/sprites";
+// Config
+pub const CONFIG_FILE_PATH: &str = "./config.cfg";
 
-// Debugging
-pub const DEFAULT_DEBUGGING_STATE: bool = true;
-pub const DEBUG_FONT_PATH: &str = "./assets/fonts/debug.ttf";
-pub const DEBUG_FONT_POINT_SIZE: u16 = 15;
-pub const DEBUG_COLOR: Color = Color::MAGENTA;
-pub const DEFAULT_DEBUG_OPTIONS: DebugOptions = DebugOptions {
-    game_state: true,
-    map_view: true,
-};
+// Chat
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 120;
+pub const MAX_CHAT_MESSAGES: usize = 5;
+pub const CHAT_RATE_WINDOW_MS: u16 = 10_000;
 
-// Font
-pub const GAME_FONT_PATH: &str = "./assets/fonts/game.ttf";
-pub const GAME_FONT_POINT_SIZE: u16 = 15;
+// Bots
+// Chance per tick that a bot picks a new direction
+pub const BOT_TURN_CHANCE: f64 = 0.005;
 
 // Map
 pub const MAP_WIDTH: u32 = 7000;
 pub const MAP_HEIGHT: u32 = 7000;
+pub const SPATIAL_CELL_SIZE: u32 = 250;
+// Extra room around the view in which entities are still drawn
+pub const CULLING_MARGIN: u32 = 50;
 
 // Player
 pub const INITIAL_PLAYER_SCORE: u32 = 20;
+pub const DEFAULT_PLAYER_NAME: &str = "Unnamed cell";
+pub const MAX_PLAYER_NAME_LENGTH: usize = 15;
+pub const MAX_SERVER_ADDRESS_LENGTH: usize = 64;
+// Cursor distance from the cell at which it reaches full speed
+pub const SPEED_DISTANCE_CAP: f32 = 150.0;
+// Cursor distance from the cell under which it stands still
+pub const STEERING_DEAD_ZONE: f32 = 1.0;
+pub const MAX_BODY_PARTS: usize = 16;
+pub const MIN_SPLIT_RADIUS: u32 = 35;
+pub const MERGE_DELAY_MS: u16 = 15_000;
+pub const MIN_EJECT_RADIUS: u32 = 30;
+pub const EJECT_RADIUS_COST: u32 = 2;
+pub const MAX_PLAYER_INPUTS_PER_SECOND: u16 = 120;
+// How much bigger than another body part one must be to eat it
+pub const EAT_RADIUS_RATIO: f32 = 1.1;
+// Most a player can be given with the `mass` console command, about as wide as the map
+pub const MAX_MASS: u32 = 500_000;
+// Body parts bigger than this lose a unit of radius every settings::DECAY_INTERVAL_TICKS ticks
+pub const DECAY_MIN_RADIUS: u32 = 150;
+pub const DECAY_INTERVAL_TICKS: u64 = 60;
+// Bright, well separated hues that stand out on the dark background
+pub const PLAYER_COLORS: [PlayerColor; 12] = [
+    PlayerColor { r: 255, g: 75, b: 75 },
+    PlayerColor { r: 255, g: 150, b: 40 },
+    PlayerColor { r: 250, g: 225, b: 50 },
+    PlayerColor { r: 170, g: 240, b: 60 },
+    PlayerColor { r: 60, g: 220, b: 110 },
+    PlayerColor { r: 50, g: 230, b: 210 },
+    PlayerColor { r: 70, g: 190, b: 255 },
+    PlayerColor { r: 120, g: 140, b: 255 },
+    PlayerColor { r: 180, g: 110, b: 255 },
+    PlayerColor { r: 240, g: 90, b: 230 },
+    PlayerColor { r: 255, g: 110, b: 170 },
+    PlayerColor { r: 235, g: 235, b: 235 },
+];
 
 // Fruits
 pub const FRUITS: u16 = 600;
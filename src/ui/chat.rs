@@ -0,0 +1,221 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::BlendMode;
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::Sender;
+
+use agario::time;
+use super::settings;
+use agario::helper::U2GMessage;
+use super::error::UiError;
+use agario::game::chat::ChatMessage;
+
+use super::{Ui, TextureCreator};
+
+const CHAT_LINE_HEIGHT: i32 = 18;
+const CHAT_MARGIN: i32 = 10;
+
+struct ChatLine {
+    // None for notices from the game itself
+    message: Option<ChatMessage>,
+    text: String,
+    received_timestamp: u128,
+}
+
+// Chat box in the bottom left corner, Enter starts typing, Enter again sends
+#[derive(Default)]
+pub struct Chat {
+    pub typing: bool,
+    pub input: String,
+    lines: Vec<ChatLine>,
+    // How many lines up from the newest one the box is scrolled, only while typing
+    scroll: usize,
+}
+
+impl Chat {
+    pub fn receive(&mut self, message: ChatMessage) {
+        self.push(ChatLine {
+            text: message.text.clone(),
+            message: Some(message),
+            received_timestamp: time::now(),
+        });
+    }
+
+    pub fn notice(&mut self, text: &str) {
+        self.push(ChatLine {
+            message: None,
+            text: text.to_string(),
+            received_timestamp: time::now(),
+        });
+    }
+
+    fn push(&mut self, line: ChatLine) {
+        self.lines.push(line);
+
+        let overflow = self.lines.len().saturating_sub(settings::CHAT_SCROLLBACK);
+        self.lines.drain(..overflow);
+    }
+
+    // Fully visible until settings::CHAT_FADE_DELAY_MS, then fades out over settings::CHAT_FADE_MS
+    fn alpha(&self, line: &ChatLine) -> u8 {
+        if self.typing {
+            return 255;
+        }
+
+        let age = time::now().saturating_sub(line.received_timestamp);
+        let fade_start = time::to_nano(settings::CHAT_FADE_DELAY_MS);
+        let fade = time::to_nano(settings::CHAT_FADE_MS);
+
+        if age < fade_start {
+            return 255;
+        }
+
+        let faded = (age - fade_start).min(fade) as f32 / fade as f32;
+        return (255.0 * (1.0 - faded)) as u8;
+    }
+}
+
+impl Ui {
+    // Enter opens the chat box. While typing every key is text, the backtick too,
+    // Enter sends the message and Escape drops it
+    pub fn chat_events(&mut self, event: &Event, tx: &Sender<U2GMessage>) -> Result<bool, UiError> {
+        if !self.chat.typing {
+            if let Event::KeyDown { keycode: Some(Keycode::Return | Keycode::KpEnter), .. } = event {
+                self.chat.typing = true;
+                self.video_subsystem.text_input().start();
+                return Ok(true);
+            }
+            return Ok(false);
+        }
+
+        match event {
+            Event::TextInput { text, .. } => {
+                for character in text.chars() {
+                    if self.chat.input.chars().count() >= settings::MAX_CHAT_MESSAGE_LENGTH {
+                        break;
+                    }
+                    self.chat.input.push(character);
+                }
+            }
+            Event::KeyDown { keycode: Some(keycode), .. } => {
+                match *keycode {
+                    Keycode::Backspace => {
+                        self.chat.input.pop();
+                    }
+                    Keycode::PageUp => {
+                        let max_scroll = self.chat.lines.len().saturating_sub(settings::CHAT_VISIBLE_LINES);
+                        self.chat.scroll = (self.chat.scroll + settings::CHAT_VISIBLE_LINES).min(max_scroll);
+                    }
+                    Keycode::PageDown => {
+                        self.chat.scroll = self.chat.scroll.saturating_sub(settings::CHAT_VISIBLE_LINES);
+                    }
+                    Keycode::Return | Keycode::KpEnter | Keycode::Escape => {
+                        let text = std::mem::take(&mut self.chat.input);
+                        let send = *keycode != Keycode::Escape && !text.trim().is_empty();
+
+                        if let (true, Some(player_id)) = (send, &self.player_id) {
+                            tx.send(U2GMessage::Chat(*player_id, text))?;
+                        }
+
+                        self.chat.typing = false;
+                        self.chat.scroll = 0;
+                        self.video_subsystem.text_input().stop();
+                    }
+                    _ => {}
+                }
+            }
+            Event::MouseWheel { y, .. } => {
+                let max_scroll = self.chat.lines.len().saturating_sub(settings::CHAT_VISIBLE_LINES);
+                self.chat.scroll = (self.chat.scroll as i32 + y).clamp(0, max_scroll as i32) as usize;
+            }
+            // Key releases still reach the controls so nothing stays held down while typing
+            _ => return Ok(false),
+        }
+
+        return Ok(true);
+    }
+
+    // Renders `text` with the given opacity and returns how wide it was
+    pub fn write_faded_text(
+        &mut self,
+        text: &str,
+        color: Color,
+        alpha: u8,
+        position: Point,
+        font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<i32, UiError> {
+        if text.is_empty() {
+            return Ok(0);
+        }
+
+        let surface = font.render(text).blended(color)?;
+        let mut texture = texture_creator.create_texture_from_surface(&surface)?;
+        texture.set_alpha_mod(alpha);
+
+        let width = surface.width();
+        self.canvas.copy(&texture, None, Some(Rect::new(position.x(), position.y(), width, surface.height())))?;
+
+        return Ok(width as i32);
+    }
+
+    pub fn draw_chat(&mut self, font: &Font, texture_creator: &TextureCreator) -> Result<(), UiError> {
+        let (_, height) = self.canvas.output_size()?;
+        let visible_lines = settings::CHAT_VISIBLE_LINES as i32;
+        let input_top = height as i32 - CHAT_MARGIN - CHAT_LINE_HEIGHT;
+        let top = input_top - visible_lines * CHAT_LINE_HEIGHT;
+
+        if self.chat.typing {
+            self.canvas.set_blend_mode(BlendMode::Blend);
+            self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 150));
+            self.canvas.fill_rect(Rect::new(
+                0,
+                top - CHAT_MARGIN / 2,
+                400,
+                ((visible_lines + 1) * CHAT_LINE_HEIGHT + CHAT_MARGIN) as u32,
+            ))?;
+            self.canvas.set_blend_mode(BlendMode::None);
+
+            self.write_faded_text(
+                &format!("Say: {}_", self.chat.input),
+                settings::MENU_FOCUS_COLOR,
+                255,
+                Point::new(CHAT_MARGIN, input_top),
+                font,
+                texture_creator,
+            )?;
+        }
+
+        let last_line = self.chat.lines.len() - self.chat.scroll;
+        let first_line = last_line.saturating_sub(settings::CHAT_VISIBLE_LINES);
+        let shown = last_line - first_line;
+
+        for line_i in first_line..last_line {
+            let line = &self.chat.lines[line_i];
+            let alpha = self.chat.alpha(line);
+            if alpha == 0 {
+                continue;
+            }
+
+            let (prefix, prefix_color, text_color) = match &line.message {
+                Some(message) => (
+                    format!("{}: ", message.name),
+                    Color::RGB(message.color.r, message.color.g, message.color.b),
+                    Color::WHITE,
+                ),
+                None => (String::new(), Color::GRAY, Color::GRAY),
+            };
+            let text = line.text.clone();
+
+            // The newest line sits right above the input
+            let y = input_top - (shown - (line_i - first_line)) as i32 * CHAT_LINE_HEIGHT;
+            let prefix_width = self.write_faded_text(&prefix, prefix_color, alpha, Point::new(CHAT_MARGIN, y), font, texture_creator)?;
+            self.write_faded_text(&text, text_color, alpha, Point::new(CHAT_MARGIN + prefix_width, y), font, texture_creator)?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,143 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::Sender;
+
+use super::settings;
+use agario::helper::U2GMessage;
+use super::error::UiError;
+use agario::geometry::Position;
+
+use super::{Ui, TextureCreator};
+
+const CONSOLE_LINE_HEIGHT: u16 = 18;
+
+// Drop-down console sending commands to the game, see game::console for the list
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    // Commands typed and their answers, oldest first
+    pub lines: Vec<String>,
+}
+
+impl Console {
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.lines.push(line.to_string());
+        }
+
+        let overflow = self.lines.len().saturating_sub(settings::CONSOLE_SCROLLBACK);
+        self.lines.drain(..overflow);
+    }
+}
+
+impl Ui {
+    // The backtick opens and closes the console. While open it keeps every key to itself,
+    // except Escape and key releases
+    pub fn console_events(&mut self, event: &Event, tx: &Sender<U2GMessage>) -> Result<bool, UiError> {
+        // While typing in the chat the backtick is just a character, and the chat keeps the text input
+        if self.chat.typing {
+            return Ok(false);
+        }
+
+        if let Event::KeyDown { keycode: Some(Keycode::Backquote), .. } = event {
+            self.console.open = !self.console.open;
+
+            let text_input = self.video_subsystem.text_input();
+            if self.console.open {
+                text_input.start();
+            } else {
+                text_input.stop();
+            }
+            return Ok(true);
+        }
+
+        if !self.console.open {
+            return Ok(false);
+        }
+
+        match event {
+            Event::TextInput { text, .. } => {
+                // The backtick that opened the console comes in as text too
+                for character in text.chars().filter(|character| *character != '`') {
+                    if self.console.input.chars().count() >= settings::MAX_CONSOLE_INPUT_LENGTH {
+                        break;
+                    }
+                    self.console.input.push(character);
+                }
+            }
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => {
+                self.console.input.pop();
+            }
+            Event::KeyDown { keycode: Some(Keycode::Return | Keycode::KpEnter), .. } => {
+                let line = std::mem::take(&mut self.console.input);
+                if line.trim().is_empty() {
+                    return Ok(true);
+                }
+
+                self.console.print(&format!("> {}", line));
+                match &self.player_id {
+                    Some(player_id) => {
+                        tx.send(U2GMessage::Command(*player_id, line))?;
+                    }
+                    None => self.console.print("Error: Not connected"),
+                }
+            }
+            // Escape still pauses and key releases still reach the controls
+            // so nothing stays held down while typing
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } | Event::KeyUp { .. } => return Ok(false),
+            Event::KeyDown { .. } => {}
+            _ => return Ok(false),
+        }
+
+        return Ok(true);
+    }
+
+    pub fn draw_console(&mut self, font: &Font, texture_creator: &TextureCreator) -> Result<(), UiError> {
+        if !self.console.open {
+            return Ok(());
+        }
+
+        let (width, _) = self.canvas.output_size()?;
+        let lines = settings::CONSOLE_VISIBLE_LINES;
+        let panel = Rect::new(
+            0,
+            0,
+            width,
+            (lines as u32 + 1) * CONSOLE_LINE_HEIGHT as u32 + 16,
+        );
+
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 210));
+        self.canvas.fill_rect(panel)?;
+        self.canvas.set_blend_mode(BlendMode::None);
+
+        let first_line = self.console.lines.len().saturating_sub(lines);
+        let text = self.console.lines[first_line..].join("\n");
+        self.write_text(
+            &text,
+            Color::WHITE,
+            Position { x: 8.0, y: 8.0 },
+            font,
+            texture_creator,
+            Some(CONSOLE_LINE_HEIGHT),
+        )?;
+
+        self.write_text(
+            &format!("] {}_", self.console.input),
+            settings::MENU_FOCUS_COLOR,
+            Position {
+                x: 8.0,
+                y: 8.0 + (lines as u16 * CONSOLE_LINE_HEIGHT) as f32,
+            },
+            font,
+            texture_creator,
+            None,
+        )
+    }
+}
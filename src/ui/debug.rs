@@ -0,0 +1,441 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::BlendMode;
+use sdl2::ttf::Font;
+
+use std::collections::VecDeque;
+
+use super::settings;
+use super::error::UiError;
+use agario::game::{Game, map::MapView, player::Player, spatial::SpatialGrid};
+use agario::geometry::{Position, circle::Circle, rectangle::{Rectangle, RectangleSize, Size}};
+
+use super::{Ui, DebugOptions, TextureCreator};
+
+const FRAME_GRAPH_SAMPLES: usize = 120;
+const FRAME_GRAPH_HEIGHT: i32 = 60;
+const FRAME_GRAPH_PIXELS_PER_MS: f32 = 2.0;
+
+// What the UI measured about itself, shown by the debug overlay
+pub struct DebugStats {
+    // States waiting in the channel when the last frame drained it
+    pub queue_depth: usize,
+    pub render_time: u128,
+    pub frame_times: VecDeque<u128>,
+}
+
+impl Default for DebugStats {
+    fn default() -> DebugStats {
+        return DebugStats::new();
+    }
+}
+
+impl DebugStats {
+    pub fn new() -> DebugStats {
+        DebugStats {
+            queue_depth: 0,
+            render_time: 0,
+            frame_times: VecDeque::with_capacity(FRAME_GRAPH_SAMPLES),
+        }
+    }
+
+    pub fn push_frame_time(&mut self, frame_time: u128) {
+        if self.frame_times.len() == FRAME_GRAPH_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+}
+
+fn milliseconds(nanoseconds: u128) -> String {
+    format!("{:.2}ms", nanoseconds as f64 / 1_000_000.0)
+}
+
+impl Ui {
+    pub fn debug_events(&mut self, event: &Event) {
+        match event {
+            Event::KeyDown {
+                keycode: Some(Keycode::F1),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    collision_shapes: !self.debug_options.collision_shapes,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F2),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    vectors: !self.debug_options.vectors,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F3),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    spatial_grid: !self.debug_options.spatial_grid,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F4),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    culling: !self.debug_options.culling,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F5),
+                ..
+            } => {
+                self.debugging = !self.debugging;
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F6),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    game_state: !self.debug_options.game_state,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F7),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    map_view: !self.debug_options.map_view,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F8),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    raw_states: !self.debug_options.raw_states,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F9),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    timings: !self.debug_options.timings,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F10),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    entities: !self.debug_options.entities,
+                    ..self.debug_options
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::F11),
+                ..
+            } => {
+                self.debug_options = DebugOptions {
+                    frame_graph: !self.debug_options.frame_graph,
+                    ..self.debug_options
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    pub fn debug(
+        &mut self,
+        game: &mut Game,
+        debug_font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        if self.debug_options.spatial_grid {
+            self.draw_spatial_grid(game)?;
+        }
+
+        if self.debug_options.collision_shapes {
+            self.draw_collision_shapes(game)?;
+        }
+
+        if self.debug_options.vectors {
+            self.draw_vectors(game)?;
+        }
+
+        if self.debug_options.culling {
+            self.draw_culling()?;
+        }
+
+        let mut info_text = String::new();
+
+        if self.debug_options.game_state {
+            let fps = game.fps;
+            let ui_fps = self.fps;
+            let dropped_inputs = game.input_limiter.dropped;
+            let merged_inputs = game.input_limiter.merged;
+            let rendering = if self.debug_options.raw_states { "raw" } else { "interpolated" };
+
+            info_text += &format!("FPS: {fps}\nUI FPS: {ui_fps}\nDropped inputs: {dropped_inputs}\nMerged inputs: {merged_inputs}\nRendering: {rendering} (F8)\n");
+        }
+
+        if self.debug_options.timings {
+            let timings = game.timings;
+
+            info_text += &format!(
+                "Tick: {}\n  Movement: {}\n  Collisions: {}\n  Snapshot: {}\nRender: {}\nQueued states: {}\n",
+                milliseconds(timings.tick),
+                milliseconds(timings.movement),
+                milliseconds(timings.collisions),
+                milliseconds(timings.snapshot),
+                milliseconds(self.debug_stats.render_time),
+                self.debug_stats.queue_depth,
+            );
+        }
+
+        if self.debug_options.entities {
+            info_text += &format!(
+                "Players: {}\nFruits: {}\nObstacles: {}\n",
+                game.map.players.len(),
+                game.map.world.fruits().count(),
+                game.map.obstacles.len(),
+            );
+
+            if let Some(player) = Player::get(self.player_id, game) {
+                let body_parts = player.cells(&game.map.world);
+                let mass: u32 = body_parts.iter().map(|body_part| Player::mass(body_part)).sum();
+                let position = body_parts.first().map_or(Position { x: 0.0, y: 0.0 }, |body_part| body_part.center);
+
+                info_text += &format!(
+                    "Mass: {mass}\nPosition: {:.0}, {:.0}\nBody parts: {}\n",
+                    position.x,
+                    position.y,
+                    player.body_parts.len(),
+                );
+            }
+        }
+
+        self.write_text(
+            &info_text,
+            settings::DEBUG_COLOR,
+            Position {
+                x: 10.0,
+                y: 10.0,
+            },
+            debug_font,
+            texture_creator,
+            None,
+        )?;
+
+        if self.debug_options.frame_graph {
+            self.draw_frame_graph()?;
+        }
+
+        if self.debug_options.map_view {
+            self.canvas.set_draw_color(Color::GREEN);
+            let size = Rectangle::to_rectangle_size(self.map_view.size.clone());
+            let pos = self.map_view.map_position(self.map_view.position);
+            self.canvas.draw_rect(
+                sdl2::rect::Rect::from_center(
+                    sdl2::rect::Point::from((
+                        pos.x as i32,
+                        pos.y as i32,
+                    )),
+                    size.width - 5,
+                    size.height - 5,
+                )
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    // The view entities are culled against, shrunk while debugging the culling
+    pub fn culling_view(&self) -> MapView {
+        if !(self.debugging && self.debug_options.culling) {
+            return self.map_view.clone();
+        }
+
+        let size = Rectangle::to_rectangle_size(self.map_view.size.clone());
+        let inset = settings::DEBUG_CULLING_INSET * 2;
+
+        MapView {
+            position: self.map_view.position,
+            size: Size::Rectangle(RectangleSize {
+                width: size.width.saturating_sub(inset),
+                height: size.height.saturating_sub(inset),
+            }),
+        }
+    }
+
+    fn draw_rectangle(&mut self, rectangle: &Rectangle, color: Color) -> Result<(), UiError> {
+        let corners = rectangle.get_corners();
+        let top_left = self.map_view.map_position(corners.top_left);
+        let bottom_right = self.map_view.map_position(corners.bottom_right);
+
+        self.canvas.set_draw_color(color);
+        self.canvas.draw_rect(Rect::new(
+            top_left.x as i32,
+            top_left.y as i32,
+            (bottom_right.x - top_left.x) as u32,
+            (bottom_right.y - top_left.y) as u32,
+        ))?;
+
+        return Ok(());
+    }
+
+    // The shrunk view in green and the culling area around it, margin included, in orange
+    fn draw_culling(&mut self) -> Result<(), UiError> {
+        let culling_view = self.culling_view();
+
+        self.draw_rectangle(&culling_view.clone(), Color::GREEN)?;
+        self.draw_rectangle(&culling_view.culling_rectangle(), Color::RGB(255, 140, 0))
+    }
+
+    fn draw_collision_shapes(&mut self, game: &Game) -> Result<(), UiError> {
+        let culling_view = self.culling_view();
+        let fruits = culling_view.get_visible_fruits(&game.map);
+        let body_parts: Vec<Circle> = game.map.players
+            .values()
+            .filter(|player| culling_view.is_player_visible(player, &game.map))
+            .flat_map(|player| player.cells(&game.map.world).into_iter().map(|body_part| body_part.circle))
+            .collect();
+
+        for circle in fruits.iter().chain(body_parts.iter()) {
+            self.circle(
+                Circle {
+                    center: self.map_view.map_position(circle.center),
+                    ..*circle
+                },
+                settings::DEBUG_COLOR,
+                false,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    // Yellow: where the player steers to, cyan: how far each body part moves in settings::DEBUG_VELOCITY_SCALE seconds
+    fn draw_vectors(&mut self, game: &Game) -> Result<(), UiError> {
+        let culling_view = self.culling_view();
+        let players: Vec<Player> = game.map.players
+            .values()
+            .filter(|player| culling_view.is_player_visible(player, &game.map))
+            .cloned()
+            .collect();
+
+        for player in players {
+            let step = Game::player_step(game.tick_rate, &player, &game.map.world);
+            let velocity_scale = game.tick_rate as f32 * settings::DEBUG_VELOCITY_SCALE;
+
+            for body_part in player.cells(&game.map.world) {
+                let center = self.map_view.map_position(body_part.center);
+
+                self.canvas.set_draw_color(Color::YELLOW);
+                self.canvas.draw_line(
+                    Point::new(center.x as i32, center.y as i32),
+                    Point::new((center.x + player.direction.x) as i32, (center.y + player.direction.y) as i32),
+                )?;
+
+                self.canvas.set_draw_color(Color::CYAN);
+                self.canvas.draw_line(
+                    Point::new(center.x as i32, center.y as i32),
+                    Point::new(
+                        (center.x + step.x * velocity_scale) as i32,
+                        (center.y + step.y * velocity_scale) as i32,
+                    ),
+                )?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Shades the collision grid cells in view, the more edible entities a cell holds the brighter it is.
+    // Snapshots don't carry the collision system's grid, so the same one is built here
+    fn draw_spatial_grid(&mut self, game: &Game) -> Result<(), UiError> {
+        let world = &game.map.world;
+        let mut grid = SpatialGrid::new(world.width, world.height, settings::SPATIAL_CELL_SIZE);
+        for (entity, _) in world.edibles.iter() {
+            if let Some(position) = world.positions.get(entity) {
+                grid.insert(entity, *position);
+            }
+        }
+
+        let cell_size = grid.cell_size as f32;
+        let corners = self.map_view.get_corners();
+
+        let first_column = (corners.top_left.x.max(0.0) / cell_size) as u32;
+        let first_row = (corners.top_left.y.max(0.0) / cell_size) as u32;
+        let last_column = ((corners.bottom_right.x.max(0.0) / cell_size) as u32).min(grid.columns - 1);
+        let last_row = ((corners.bottom_right.y.max(0.0) / cell_size) as u32).min(grid.rows - 1);
+
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                let top_left = self.map_view.map_position(Position {
+                    x: column as f32 * cell_size,
+                    y: row as f32 * cell_size,
+                });
+                let cell = Rect::new(top_left.x as i32, top_left.y as i32, grid.cell_size, grid.cell_size);
+
+                let entities = grid.cell(column, row).len() as u32;
+                self.canvas.set_draw_color(Color::RGBA(0, 160, 255, (entities * 20).min(160) as u8));
+                self.canvas.fill_rect(cell)?;
+                self.canvas.set_draw_color(Color::RGBA(0, 160, 255, 80));
+                self.canvas.draw_rect(cell)?;
+            }
+        }
+        self.canvas.set_blend_mode(BlendMode::None);
+
+        return Ok(());
+    }
+
+    // One bar per frame in the bottom left corner, the line marks the frame budget of settings::MAX_UI_FPS
+    fn draw_frame_graph(&mut self) -> Result<(), UiError> {
+        let (_, height) = self.canvas.output_size()?;
+        let bottom = height as i32 - 10;
+        let left = 10;
+
+        self.canvas.set_draw_color(Color::RGB(20, 20, 20));
+        self.canvas.fill_rect(Rect::new(
+            left,
+            bottom - FRAME_GRAPH_HEIGHT,
+            FRAME_GRAPH_SAMPLES as u32,
+            FRAME_GRAPH_HEIGHT as u32,
+        ))?;
+
+        self.canvas.set_draw_color(settings::DEBUG_COLOR);
+        for (sample_i, frame_time) in self.debug_stats.frame_times.iter().enumerate() {
+            let bar_height = (*frame_time as f32 / 1_000_000.0 * FRAME_GRAPH_PIXELS_PER_MS) as i32;
+            let x = left + sample_i as i32;
+
+            self.canvas.draw_line(
+                Point::new(x, bottom),
+                Point::new(x, bottom - bar_height.min(FRAME_GRAPH_HEIGHT)),
+            )?;
+        }
+
+        let budget_ms = 1000.0 / settings::MAX_UI_FPS as f32;
+        let budget_y = bottom - (budget_ms * FRAME_GRAPH_PIXELS_PER_MS) as i32;
+        self.canvas.set_draw_color(Color::GREEN);
+        self.canvas.draw_line(
+            Point::new(left, budget_y),
+            Point::new(left + FRAME_GRAPH_SAMPLES as i32, budget_y),
+        )?;
+
+        return Ok(());
+    }
+}
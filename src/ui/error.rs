@@ -0,0 +1,82 @@
+use std::fmt;
+use std::sync::mpsc::SendError;
+
+use sdl2::IntegerOrSdlError;
+use sdl2::render::TextureValueError;
+use sdl2::ttf::{FontError, InitError};
+use sdl2::video::WindowBuildError;
+
+use agario::helper::U2GMessage;
+
+pub use agario::error::*;
+
+#[derive(Debug)]
+pub enum UiError {
+    // SDL reports most of its errors as plain strings
+    Sdl(String),
+    Font {
+        path: String,
+        message: String,
+    },
+    Transport(TransportError),
+}
+
+impl fmt::Display for UiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UiError::Sdl(message) => write!(f, "SDL error: {}", message),
+            UiError::Font { path, message } => write!(f, "Couldn't load the font {}: {}", path, message),
+            UiError::Transport(error) => write!(f, "UI stopped, {}", error),
+        }
+    }
+}
+
+impl std::error::Error for UiError {}
+
+impl From<TransportError> for UiError {
+    fn from(error: TransportError) -> UiError {
+        UiError::Transport(error)
+    }
+}
+
+impl From<SendError<U2GMessage>> for UiError {
+    fn from(error: SendError<U2GMessage>) -> UiError {
+        UiError::Transport(error.into())
+    }
+}
+
+impl From<String> for UiError {
+    fn from(message: String) -> UiError {
+        UiError::Sdl(message)
+    }
+}
+
+impl From<WindowBuildError> for UiError {
+    fn from(error: WindowBuildError) -> UiError {
+        UiError::Sdl(error.to_string())
+    }
+}
+
+impl From<IntegerOrSdlError> for UiError {
+    fn from(error: IntegerOrSdlError) -> UiError {
+        UiError::Sdl(error.to_string())
+    }
+}
+
+impl From<TextureValueError> for UiError {
+    fn from(error: TextureValueError) -> UiError {
+        UiError::Sdl(error.to_string())
+    }
+}
+
+impl From<FontError> for UiError {
+    fn from(error: FontError) -> UiError {
+        UiError::Sdl(error.to_string())
+    }
+}
+
+impl From<InitError> for UiError {
+    fn from(error: InitError) -> UiError {
+        UiError::Sdl(error.to_string())
+    }
+}
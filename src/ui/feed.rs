@@ -0,0 +1,148 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Point;
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::Sender;
+
+use agario::time;
+use super::settings;
+use agario::helper::U2GMessage;
+use super::error::UiError;
+use agario::game::{events::{GameEvent, TickEvents}, player::Player};
+use agario::geometry::{Position, circle::Circle};
+
+use super::{Ui, TextureCreator};
+
+const FEED_LINE_HEIGHT: i32 = 18;
+const FEED_MARGIN: i32 = 10;
+
+struct FeedLine {
+    text: String,
+    // Lines about the player themselves stand out
+    own: bool,
+    received_timestamp: u128,
+}
+
+// Ring growing out of a spot where a cell got eaten
+struct Effect {
+    position: Position,
+    radius: u32,
+    started_timestamp: u128,
+}
+
+// Kill feed in the bottom right corner and the effects drawn over the map, both fed by game events
+#[derive(Default)]
+pub struct Feed {
+    lines: Vec<FeedLine>,
+    effects: Vec<Effect>,
+}
+
+impl Feed {
+    fn push(&mut self, text: String, own: bool) {
+        self.lines.push(FeedLine {
+            text,
+            own,
+            received_timestamp: time::now(),
+        });
+
+        let overflow = self.lines.len().saturating_sub(settings::KILL_FEED_LINES);
+        self.lines.drain(..overflow);
+    }
+
+    // Same timing as the chat: visible for settings::CHAT_FADE_DELAY_MS, then fading out
+    fn alpha(line: &FeedLine) -> u8 {
+        let age = time::now().saturating_sub(line.received_timestamp);
+        let fade_start = time::to_nano(settings::CHAT_FADE_DELAY_MS);
+        let fade = time::to_nano(settings::CHAT_FADE_MS);
+
+        if age < fade_start {
+            return 255;
+        }
+
+        let faded = (age - fade_start).min(fade) as f32 / fade as f32;
+        return (255.0 * (1.0 - faded)) as u8;
+    }
+}
+
+impl Ui {
+    pub fn receive_events(&mut self, tick_events: TickEvents, tx: &Sender<U2GMessage>) -> Result<(), UiError> {
+        for event in tick_events.events {
+            match event {
+                GameEvent::CellEaten { position, mass, .. } => {
+                    self.feed.effects.push(Effect {
+                        position,
+                        // Back from mass to the radius of the eaten cell
+                        radius: Player::radius(mass),
+                        started_timestamp: time::now(),
+                    });
+                }
+                GameEvent::PlayerDied { player_id, name, killer_id, killer_name } => {
+                    let died = self.player_id == Some(player_id);
+                    let killed = killer_id.is_some() && self.player_id == killer_id;
+
+                    let text = match killer_name {
+                        Some(killer_name) => format!("{} ate {}", killer_name, name),
+                        None => format!("{} was killed", name),
+                    };
+                    self.feed.push(text, died || killed);
+
+                    // Joins again right away with the same look
+                    if died {
+                        self.player_id = None;
+                        self.selection = None;
+                        self.join(tx)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn draw_effects(&mut self) -> Result<(), UiError> {
+        let duration = time::to_nano(settings::EFFECT_DURATION_MS);
+        let now = time::now();
+        self.feed.effects.retain(|effect| now.saturating_sub(effect.started_timestamp) < duration);
+
+        for effect_i in 0..self.feed.effects.len() {
+            let effect = &self.feed.effects[effect_i];
+            let progress = now.saturating_sub(effect.started_timestamp) as f32 / duration as f32;
+            let circle = Circle {
+                center: self.map_view.map_position(effect.position),
+                radius: (effect.radius as f32 * (1.0 + progress)) as u32,
+            };
+
+            self.circle(circle, Color::RGBA(255, 255, 255, (255.0 * (1.0 - progress)) as u8), false)?;
+        }
+
+        return Ok(());
+    }
+
+    pub fn draw_feed(&mut self, font: &Font, texture_creator: &TextureCreator) -> Result<(), UiError> {
+        let (width, height) = self.canvas.output_size()?;
+        let bottom = height as i32 - FEED_MARGIN - FEED_LINE_HEIGHT;
+        let lines = self.feed.lines.len();
+
+        for line_i in 0..lines {
+            let line = &self.feed.lines[line_i];
+            let alpha = Feed::alpha(line);
+            if alpha == 0 {
+                continue;
+            }
+
+            let color = if line.own { settings::MENU_FOCUS_COLOR } else { Color::WHITE };
+            let text = line.text.clone();
+            let (text_width, _) = font.size_of(&text)?;
+
+            // Newest at the bottom, right aligned
+            let position = Point::new(
+                width as i32 - FEED_MARGIN - text_width as i32,
+                bottom - (lines - 1 - line_i) as i32 * FEED_LINE_HEIGHT,
+            );
+            self.write_faded_text(&text, color, alpha, position, font, texture_creator)?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,44 @@
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+// Lazily loads one font file at many point sizes, both plain and outlined.
+// Sizes are rounded to multiples of `size_step` so only a handful get loaded.
+pub struct FontCache<'ttf> {
+    ttf_context: &'ttf Sdl2TtfContext,
+    path: String,
+    size_step: u16,
+    fonts: HashMap<(u16, u16), Font<'ttf, 'static>>,
+}
+
+impl<'ttf> FontCache<'ttf> {
+    pub fn new(ttf_context: &'ttf Sdl2TtfContext, path: &str, size_step: u16) -> FontCache<'ttf> {
+        FontCache {
+            ttf_context,
+            path: path.to_string(),
+            size_step: size_step.max(1),
+            fonts: HashMap::new(),
+        }
+    }
+
+    pub fn rounded_size(&self, point_size: u16) -> u16 {
+        let steps = (point_size + self.size_step / 2) / self.size_step;
+        return steps.max(1) * self.size_step;
+    }
+
+    pub fn get(&mut self, point_size: u16, outline: u16) -> Option<&Font<'ttf, 'static>> {
+        let point_size = self.rounded_size(point_size);
+        let key = (point_size, outline);
+
+        if !self.fonts.contains_key(&key) {
+            let mut font = self.ttf_context
+                .load_font(Path::new(&self.path), point_size)
+                .ok()?;
+            font.set_outline_width(outline);
+            self.fonts.insert(key, font);
+        }
+
+        return self.fonts.get(&key);
+    }
+}
@@ -0,0 +1,374 @@
+use sdl2::GameControllerSubsystem;
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use std::collections::{HashMap, HashSet};
+
+use super::settings;
+use agario::warn;
+use agario::config::Config;
+use agario::helper::PlayerEvent;
+use agario::geometry::Position;
+
+#[derive(Clone, Debug)]
+pub struct Bindings {
+    pub up: Vec<Keycode>,
+    pub down: Vec<Keycode>,
+    pub left: Vec<Keycode>,
+    pub right: Vec<Keycode>,
+    pub split: Vec<Keycode>,
+    pub eject: Vec<Keycode>,
+    pub gamepad_split: Vec<Button>,
+    pub gamepad_eject: Vec<Button>,
+    pub gamepad_dead_zone: f32,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings {
+            up: vec![Keycode::W, Keycode::Up],
+            down: vec![Keycode::S, Keycode::Down],
+            left: vec![Keycode::A, Keycode::Left],
+            right: vec![Keycode::D, Keycode::Right],
+            split: vec![Keycode::Space],
+            eject: vec![Keycode::E],
+            gamepad_split: vec![Button::A],
+            gamepad_eject: vec![Button::B],
+            gamepad_dead_zone: 0.2,
+        }
+    }
+}
+
+impl Bindings {
+    pub fn new() -> Bindings {
+        return Bindings::default();
+    }
+
+    // Anything missing or unreadable in the config keeps its default binding
+    pub fn from_config(config: &Config) -> Bindings {
+        let defaults = Bindings::new();
+
+        let keys = |key: &str, default: Vec<Keycode>| {
+            parse_list(config, key, Keycode::from_name).unwrap_or(default)
+        };
+        let buttons = |key: &str, default: Vec<Button>| {
+            parse_list(config, key, Button::from_string).unwrap_or(default)
+        };
+
+        Bindings {
+            up: keys("input.up", defaults.up),
+            down: keys("input.down", defaults.down),
+            left: keys("input.left", defaults.left),
+            right: keys("input.right", defaults.right),
+            split: keys("input.split", defaults.split),
+            eject: keys("input.eject", defaults.eject),
+            gamepad_split: buttons("input.gamepad_split", defaults.gamepad_split),
+            gamepad_eject: buttons("input.gamepad_eject", defaults.gamepad_eject),
+            gamepad_dead_zone: config
+                .get_f32("input.gamepad_dead_zone")
+                .unwrap_or(defaults.gamepad_dead_zone)
+                .clamp(0.0, 0.99),
+        }
+    }
+}
+
+fn parse_list<T>(config: &Config, key: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    let names = config.get_list(key)?;
+    let mut parsed = vec![];
+
+    for name in names {
+        match parse(name) {
+            Some(value) => parsed.push(value),
+            None => {
+                warn!("ignoring unknown binding", name = name, key = key);
+            }
+        }
+    }
+
+    if parsed.is_empty() {
+        return None;
+    }
+
+    return Some(parsed);
+}
+
+pub trait InputBackend {
+    // Whether the event changed the steering
+    fn handle_event(&mut self, event: &Event) -> bool;
+
+    // Relative to the cell, which is given in screen coordinates. Backends without a target
+    // of their own go up to settings::SPEED_DISTANCE_CAP for full speed
+    fn steering(&self, cell: Position) -> Option<Position>;
+
+    // Split, eject, ... triggered since the last call
+    fn take_actions(&mut self) -> Vec<PlayerEvent>;
+}
+
+pub struct MouseInput {
+    pub position: Option<Position>,
+}
+
+impl InputBackend for MouseInput {
+    fn handle_event(&mut self, event: &Event) -> bool {
+        if let Event::MouseMotion { x, y, .. } = event {
+            self.position = Some(Position {
+                x: *x as f32,
+                y: *y as f32,
+            });
+            return true;
+        }
+
+        return false;
+    }
+
+    fn steering(&self, cell: Position) -> Option<Position> {
+        let mouse_position = self.position?;
+
+        return Some(mouse_position - cell);
+    }
+
+    fn take_actions(&mut self) -> Vec<PlayerEvent> {
+        vec![]
+    }
+}
+
+pub struct KeyboardInput {
+    pub bindings: Bindings,
+    pressed: HashSet<Keycode>,
+    actions: Vec<PlayerEvent>,
+}
+
+impl KeyboardInput {
+    pub fn new(bindings: Bindings) -> KeyboardInput {
+        KeyboardInput {
+            bindings,
+            pressed: HashSet::new(),
+            actions: vec![],
+        }
+    }
+
+    fn is_steering_key(&self, keycode: &Keycode) -> bool {
+        self.bindings.up.contains(keycode)
+        || self.bindings.down.contains(keycode)
+        || self.bindings.left.contains(keycode)
+        || self.bindings.right.contains(keycode)
+    }
+
+    fn any_pressed(&self, keycodes: &[Keycode]) -> bool {
+        keycodes.iter().any(|keycode| self.pressed.contains(keycode))
+    }
+}
+
+impl InputBackend for KeyboardInput {
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+                if self.bindings.split.contains(keycode) {
+                    self.actions.push(PlayerEvent::Split);
+                }
+                if self.bindings.eject.contains(keycode) {
+                    self.actions.push(PlayerEvent::Eject);
+                }
+                if self.is_steering_key(keycode) {
+                    self.pressed.insert(*keycode);
+                    return true;
+                }
+            }
+            Event::KeyUp { keycode: Some(keycode), .. } if self.pressed.remove(keycode) => {
+                return true;
+            }
+            _ => {}
+        }
+
+        return false;
+    }
+
+    fn steering(&self, _cell: Position) -> Option<Position> {
+        let axis = |negative: &[Keycode], positive: &[Keycode]| {
+            self.any_pressed(positive) as i8 as f32 - self.any_pressed(negative) as i8 as f32
+        };
+
+        let x = axis(&self.bindings.left, &self.bindings.right);
+        let y = axis(&self.bindings.up, &self.bindings.down);
+
+        return Some(Position::new(x, y).normalize() * settings::SPEED_DISTANCE_CAP);
+    }
+
+    fn take_actions(&mut self) -> Vec<PlayerEvent> {
+        std::mem::take(&mut self.actions)
+    }
+}
+
+pub struct GamepadInput {
+    pub bindings: Bindings,
+    subsystem: GameControllerSubsystem,
+    // Keyed by the joystick instance id SDL reports in the controller events
+    controllers: HashMap<u32, GameController>,
+    stick: Position,
+    actions: Vec<PlayerEvent>,
+}
+
+impl GamepadInput {
+    pub fn new(subsystem: GameControllerSubsystem, bindings: Bindings) -> GamepadInput {
+        GamepadInput {
+            bindings,
+            subsystem,
+            controllers: HashMap::new(),
+            stick: Position::ZERO,
+            actions: vec![],
+        }
+    }
+}
+
+impl InputBackend for GamepadInput {
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                match self.subsystem.open(*which) {
+                    Ok(controller) => {
+                        self.controllers.insert(controller.instance_id(), controller);
+                    }
+                    Err(error) => {
+                        warn!("couldn't open game controller", which = which, error = error);
+                    }
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(which);
+            }
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                let value = *value as f32 / i16::MAX as f32;
+                match axis {
+                    Axis::LeftX => self.stick.x = value,
+                    Axis::LeftY => self.stick.y = value,
+                    _ => return false,
+                }
+                return true;
+            }
+            Event::ControllerButtonDown { button, .. } => {
+                if self.bindings.gamepad_split.contains(button) {
+                    self.actions.push(PlayerEvent::Split);
+                }
+                if self.bindings.gamepad_eject.contains(button) {
+                    self.actions.push(PlayerEvent::Eject);
+                }
+            }
+            _ => {}
+        }
+
+        return false;
+    }
+
+    fn steering(&self, _cell: Position) -> Option<Position> {
+        let length = self.stick.length();
+        let dead_zone = self.bindings.gamepad_dead_zone;
+
+        if length <= dead_zone {
+            return Some(Position::ZERO);
+        }
+
+        // Rescale so the stick goes from standing still at the dead zone edge up to full speed when fully tilted
+        let magnitude = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) * settings::SPEED_DISTANCE_CAP;
+
+        return Some(self.stick / length * magnitude);
+    }
+
+    fn take_actions(&mut self) -> Vec<PlayerEvent> {
+        std::mem::take(&mut self.actions)
+    }
+}
+
+// Every backend listens all the time, the last one that changed the steering drives the cell
+pub struct Input {
+    backends: Vec<Box<dyn InputBackend>>,
+    active: usize,
+}
+
+impl Input {
+    pub fn new(bindings: Bindings, controller_subsystem: Option<GameControllerSubsystem>) -> Input {
+        let mut backends: Vec<Box<dyn InputBackend>> = vec![
+            Box::new(MouseInput { position: None }),
+            Box::new(KeyboardInput::new(bindings.clone())),
+        ];
+
+        if let Some(subsystem) = controller_subsystem {
+            backends.push(Box::new(GamepadInput::new(subsystem, bindings)));
+        }
+
+        Input {
+            backends,
+            active: 0,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        let mut steering_changed = false;
+
+        for (backend_i, backend) in self.backends.iter_mut().enumerate() {
+            if backend.handle_event(event) {
+                self.active = backend_i;
+                steering_changed = true;
+            }
+        }
+
+        return steering_changed;
+    }
+
+    pub fn steering(&self, cell: Position) -> Option<Position> {
+        self.backends[self.active].steering(cell)
+    }
+
+    pub fn take_actions(&mut self) -> Vec<PlayerEvent> {
+        self.backends
+            .iter_mut()
+            .flat_map(|backend| backend.take_actions())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_from_config() {
+        let config = Config::parse("
+            [input]
+            up = I, Up
+            split = Return
+            gamepad_eject = x, y
+            gamepad_dead_zone = 0.35
+        ");
+        let bindings = Bindings::from_config(&config);
+
+        assert_eq!(bindings.up, vec![Keycode::I, Keycode::Up]);
+        assert_eq!(bindings.split, vec![Keycode::Return]);
+        assert_eq!(bindings.gamepad_eject, vec![Button::X, Button::Y]);
+        assert_eq!(bindings.gamepad_dead_zone, 0.35);
+        // Not in the config
+        assert_eq!(bindings.down, Bindings::new().down);
+        assert_eq!(bindings.gamepad_split, Bindings::new().gamepad_split);
+    }
+
+    #[test]
+    fn unreadable_bindings_keep_the_defaults() {
+        let config = Config::parse("
+            [input]
+            up = NotAKey, W
+            down = NotAKey
+            eject =
+            gamepad_split = nope
+            gamepad_dead_zone = 7
+        ");
+        let bindings = Bindings::from_config(&config);
+        let defaults = Bindings::new();
+
+        // Unknown names are skipped, the rest of the list is kept
+        assert_eq!(bindings.up, vec![Keycode::W]);
+        assert_eq!(bindings.down, defaults.down);
+        assert_eq!(bindings.eject, defaults.eject);
+        assert_eq!(bindings.gamepad_split, defaults.gamepad_split);
+        assert_eq!(bindings.gamepad_dead_zone, 0.99);
+    }
+}
@@ -0,0 +1,192 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::Sender;
+
+use agario::time;
+use super::settings;
+use agario::helper::U2GMessage;
+use super::error::UiError;
+use agario::game::{Game, ids::{CellId, FruitId}, player::Player};
+use agario::geometry::{Position, circle::Circle};
+
+use super::{Ui, TextureCreator};
+
+const PANEL_WIDTH: u32 = 260;
+const PANEL_LINE_HEIGHT: u16 = 18;
+
+// Entity picked by clicking on it while debugging
+#[derive(Clone, Copy, Debug)]
+pub enum Selection {
+    BodyPart(CellId),
+    Fruit(FruitId),
+}
+
+impl Ui {
+    pub fn inspector_events(&mut self, event: &Event, game: Option<&Game>, tx: &Sender<U2GMessage>) -> Result<(), UiError> {
+        if !self.debugging {
+            return Ok(());
+        }
+
+        match event {
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                let game = match game {
+                    Some(game) => game,
+                    None => return Ok(()),
+                };
+
+                let position = self.map_view.world_position(Position {
+                    x: *x as f32,
+                    y: *y as f32,
+                });
+                self.selection = Ui::select(game, position);
+            }
+            Event::KeyDown { keycode: Some(Keycode::F12), .. } => {
+                // Only does something while the simulation is paused
+                tx.send(U2GMessage::Step)?;
+            }
+            _ => {}
+        }
+
+        return Ok(());
+    }
+
+    // Body parts win over fruits, and smaller body parts over the bigger ones covering them
+    fn select(game: &Game, position: Position) -> Option<Selection> {
+        let mut selected_body_part: Option<(u32, Selection)> = None;
+
+        for (cell_id, _, body_part) in game.map.world.cells() {
+            let is_smaller = match &selected_body_part {
+                Some((radius, _)) => body_part.radius < *radius,
+                None => true,
+            };
+
+            if body_part.holds(position) && is_smaller {
+                selected_body_part = Some((body_part.radius, Selection::BodyPart(cell_id)));
+            }
+        }
+
+        if let Some((_, selection)) = selected_body_part {
+            return Some(selection);
+        }
+
+        return game.map.world
+            .fruits()
+            .find(|(_, fruit)| fruit.holds(position))
+            .map(|(fruit_id, _)| Selection::Fruit(fruit_id));
+    }
+
+    fn selected_circle<'game>(&self, game: &'game Game) -> Option<(Circle, Option<&'game Player>)> {
+        match self.selection? {
+            Selection::BodyPart(cell_id) => {
+                let owner = game.map.world.owners.get(cell_id)?;
+                let player = Player::get(Some(owner.0), game)?;
+                Some((game.map.world.circle(cell_id)?, Some(player)))
+            }
+            Selection::Fruit(fruit_id) if game.map.world.is_fruit(fruit_id) => {
+                Some((game.map.world.circle(fruit_id)?, None))
+            }
+            Selection::Fruit(_) => None,
+        }
+    }
+
+    fn inspector_text(&self, game: &Game) -> String {
+        let (circle, player) = match self.selected_circle(game) {
+            Some(selected) => selected,
+            None => return String::from("Selection gone\n(eaten, merged or left)"),
+        };
+        let id = match self.selection {
+            Some(Selection::BodyPart(cell_id)) => cell_id.to_string(),
+            Some(Selection::Fruit(fruit_id)) => fruit_id.to_string(),
+            None => String::new(),
+        };
+
+        let mut text = match player {
+            Some(player) => {
+                let merge_timer = match player.merge_timestamp {
+                    Some(merge_timestamp) => format!(
+                        "{}ms",
+                        merge_timestamp.saturating_sub(time::now()) / 1_000_000
+                    ),
+                    None => String::from("-"),
+                };
+
+                format!(
+                    "Body part {} of {}\nPlayer ID: {}\nBody parts: {}\nDirection: {:.1}, {:.1}\nMerge in: {}\nPaused: {}\n",
+                    id,
+                    player.name,
+                    player.id,
+                    player.body_parts.len(),
+                    player.direction.x,
+                    player.direction.y,
+                    merge_timer,
+                    player.paused,
+                )
+            }
+            None => format!("Fruit {}\n", id),
+        };
+
+        text += &format!(
+            "Mass: {}\nRadius: {}\nPosition: {:.1}, {:.1}\nTick: {}\nF12 steps while paused",
+            Player::mass(&circle),
+            circle.radius,
+            circle.center.x,
+            circle.center.y,
+            game.tick,
+        );
+
+        return text;
+    }
+
+    // Outlines the selected entity and lists its state in a panel on the right
+    pub fn draw_inspector(&mut self, game: &Game, font: &Font, texture_creator: &TextureCreator) -> Result<(), UiError> {
+        if !self.debugging || self.selection.is_none() {
+            return Ok(());
+        }
+
+        if let Some((circle, _)) = self.selected_circle(game) {
+            self.circle(
+                Circle {
+                    center: self.map_view.map_position(circle.center),
+                    radius: circle.radius + 3,
+                },
+                Color::WHITE,
+                false,
+            )?;
+        }
+
+        let text = self.inspector_text(game);
+        let lines = text.lines().count() as u32;
+        let (width, _) = self.canvas.output_size()?;
+        let panel = Rect::new(
+            width as i32 - PANEL_WIDTH as i32 - 10,
+            10,
+            PANEL_WIDTH,
+            lines * PANEL_LINE_HEIGHT as u32 + 16,
+        );
+
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 190));
+        self.canvas.fill_rect(panel)?;
+        self.canvas.set_blend_mode(BlendMode::None);
+        self.canvas.set_draw_color(settings::DEBUG_COLOR);
+        self.canvas.draw_rect(panel)?;
+
+        self.write_text(
+            &text,
+            Color::WHITE,
+            Position {
+                x: panel.x() as f32 + 8.0,
+                y: panel.y() as f32 + 8.0,
+            },
+            font,
+            texture_creator,
+            Some(PANEL_LINE_HEIGHT),
+        )
+    }
+}
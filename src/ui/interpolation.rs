@@ -0,0 +1,195 @@
+use agario::time;
+use super::settings;
+use agario::game::{Game, ids::Entity};
+use agario::geometry::circle::Circle;
+
+struct Snapshot {
+    game: Game,
+    received_timestamp: u128,
+}
+
+// Keeps the last two simulation ticks so the UI can draw smooth motion
+// even when the game sends states slower than the display refreshes.
+pub struct Interpolator {
+    previous: Option<Snapshot>,
+    current: Option<Snapshot>,
+}
+
+impl Default for Interpolator {
+    fn default() -> Interpolator {
+        return Interpolator::new();
+    }
+}
+
+impl Interpolator {
+    pub fn new() -> Interpolator {
+        Interpolator {
+            previous: None,
+            current: None,
+        }
+    }
+
+    pub fn push(&mut self, game: Game) {
+        self.push_at(game, time::now());
+    }
+
+    fn push_at(&mut self, game: Game, received_timestamp: u128) {
+        match &mut self.current {
+            // Same tick sent again (e.g. the game is paused), nothing moved. Blending towards it
+            // would keep extrapolating the last velocities, so it's drawn as is from now on
+            Some(current) if current.game.tick == game.tick => {
+                current.game = game;
+                self.previous = None;
+            }
+            _ => {
+                self.previous = self.current.take();
+                self.current = Some(Snapshot {
+                    game,
+                    received_timestamp,
+                });
+            }
+        }
+    }
+
+    pub fn latest(&self) -> Option<&Game> {
+        self.current.as_ref().map(|snapshot| &snapshot.game)
+    }
+
+    // The state at `timestamp`, blended from the previous tick towards the latest one.
+    // Past the latest tick the motion is extrapolated for at most settings::MAX_EXTRAPOLATION_MS.
+    pub fn at(&self, timestamp: u128) -> Option<Game> {
+        let current = self.current.as_ref()?;
+        let previous = match &self.previous {
+            Some(previous) => previous,
+            None => return Some(current.game.clone()),
+        };
+
+        let tick_duration = current.game.tick_timestamp.saturating_sub(previous.game.tick_timestamp);
+        if tick_duration == 0 {
+            return Some(current.game.clone());
+        }
+
+        let elapsed = timestamp.saturating_sub(current.received_timestamp);
+        let max_elapsed = tick_duration + time::to_nano(settings::MAX_EXTRAPOLATION_MS);
+        let alpha = elapsed.min(max_elapsed) as f32 / tick_duration as f32;
+
+        let mut game = current.game.clone();
+
+        // Only entities with a velocity move. Those that just spawned (e.g. a body part that split off)
+        // have nothing to blend from and show up where they are
+        let previous_world = &previous.game.map.world;
+        let world = &mut game.map.world;
+        let entities: Vec<Entity> = world.velocities.iter().map(|(entity, _)| entity).collect();
+
+        for entity in entities {
+            let (from, to) = match (previous_world.circle(entity), world.circle(entity)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+
+            world.set_circle(entity, lerp_circle(from, to, alpha));
+        }
+
+        return Some(game);
+    }
+}
+
+fn lerp_circle(from: Circle, to: Circle, alpha: f32) -> Circle {
+    let radius = from.radius as f32 + (to.radius as f32 - from.radius as f32) * alpha;
+
+    Circle {
+        center: from.center.lerp(to.center, alpha),
+        radius: radius.round().max(0.0) as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agario::game::{Mode, arena::Arena, ecs::components::Velocity, ids::PlayerId};
+    use agario::geometry::Position;
+
+    const TICK: u128 = 10_000_000;
+
+    // A game at `tick` with a single body part at `x`, moving right
+    fn game(tick: u64, x: f32) -> (Game, Entity) {
+        let mut game = Game::new(Mode::Local);
+        let owner: PlayerId = Arena::<PlayerId, ()>::new().insert(());
+        let body_part = game.map.world.spawn_cell(owner, Circle {
+            center: Position::new(x, 100.0),
+            radius: 10,
+        });
+        game.map.world.velocities.insert(body_part, Velocity(Position::new(10.0, 0.0)));
+        game.tick = tick;
+        game.tick_timestamp = tick as u128 * TICK;
+
+        return (game, body_part);
+    }
+
+    // The tick after `game`, with the body part moved to `x`
+    fn next_tick(game: &Game, body_part: Entity, x: f32) -> Game {
+        let mut next = game.clone();
+        next.tick += 1;
+        next.tick_timestamp += TICK;
+        next.map.world.set_circle(body_part, Circle {
+            center: Position::new(x, 100.0),
+            radius: 10,
+        });
+
+        return next;
+    }
+
+    fn x_at(interpolator: &Interpolator, body_part: Entity, timestamp: u128) -> f32 {
+        return interpolator.at(timestamp).unwrap().map.world.circle(body_part).unwrap().center.x;
+    }
+
+    #[test]
+    fn blends_between_the_last_two_ticks() {
+        let mut interpolator = Interpolator::new();
+        assert!(interpolator.at(0).is_none());
+
+        let (first, body_part) = game(1, 100.0);
+        let second = next_tick(&first, body_part, 110.0);
+
+        interpolator.push_at(first, TICK);
+        // Nothing to blend from yet
+        assert_eq!(x_at(&interpolator, body_part, TICK * 5), 100.0);
+
+        interpolator.push_at(second, TICK * 2);
+        assert_eq!(x_at(&interpolator, body_part, TICK * 2), 100.0);
+        assert_eq!(x_at(&interpolator, body_part, TICK * 2 + TICK / 2), 105.0);
+        assert_eq!(x_at(&interpolator, body_part, TICK * 3), 110.0);
+        assert_eq!(interpolator.latest().unwrap().tick, 2);
+    }
+
+    #[test]
+    fn extrapolation_is_capped() {
+        let mut interpolator = Interpolator::new();
+        let (first, body_part) = game(1, 100.0);
+        let second = next_tick(&first, body_part, 110.0);
+
+        interpolator.push_at(first, TICK);
+        interpolator.push_at(second, TICK * 2);
+
+        let max_alpha = (TICK + time::to_nano(settings::MAX_EXTRAPOLATION_MS)) as f32 / TICK as f32;
+        let expected = 100.0 + 10.0 * max_alpha;
+        assert!((x_at(&interpolator, body_part, TICK * 1000) - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn repeated_ticks_are_drawn_as_is() {
+        let mut interpolator = Interpolator::new();
+        let (first, body_part) = game(1, 100.0);
+        let second = next_tick(&first, body_part, 110.0);
+
+        interpolator.push_at(first, TICK);
+        interpolator.push_at(second.clone(), TICK * 2);
+        // Paused, the same tick keeps coming
+        interpolator.push_at(second.clone(), TICK * 3);
+        interpolator.push_at(second, TICK * 50);
+
+        for timestamp in [TICK * 3, TICK * 50, TICK * 1000] {
+            assert_eq!(x_at(&interpolator, body_part, timestamp), 110.0);
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use agario::time;
+use super::settings;
+use agario::helper::G2UMessage;
+use super::error::{UiError, TransportError};
+use agario::geometry::Position;
+use agario::game::{Game, player::PlayerColor};
+
+use super::{Ui, TextureCreator};
+use super::widgets::{rect_center, BUTTON_WIDTH, BUTTON_HEIGHT};
+
+const FIELD_WIDTH: u32 = 300;
+const FIELD_HEIGHT: u32 = 34;
+const SWATCH_SIZE: u32 = 28;
+const SWATCH_GAP: u32 = 8;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuField {
+    PlayerName,
+    ServerAddress,
+}
+
+pub enum MenuAction {
+    Play,
+    Quit,
+}
+
+pub struct Menu {
+    pub player_name: String,
+    pub server_address: String,
+    // Index into settings::PLAYER_COLORS, None lets the game pick one from the player ID
+    pub color_index: Option<usize>,
+    // Names of the loaded skins, skin_index points into it
+    pub skins: Vec<String>,
+    pub skin_index: Option<usize>,
+    pub focused: Option<MenuField>,
+}
+
+struct MenuLayout {
+    title: Position,
+    player_name: Rect,
+    colors: Vec<(Option<usize>, Rect)>,
+    skin: Rect,
+    previous_skin: Rect,
+    next_skin: Rect,
+    server_address: Rect,
+    play: Rect,
+    quit: Rect,
+}
+
+impl Menu {
+    pub fn new(
+        player_name: &str,
+        color: Option<PlayerColor>,
+        skin: Option<String>,
+        skins: Vec<String>,
+        server_address: &str,
+    ) -> Menu {
+        let color_index = color.and_then(|color| {
+            settings::PLAYER_COLORS
+                .iter()
+                .position(|palette_color| *palette_color == color)
+        });
+
+        let skin_index = skin.and_then(|skin| skins.iter().position(|name| *name == skin));
+
+        Menu {
+            player_name: player_name.to_string(),
+            server_address: server_address.to_string(),
+            color_index,
+            skins,
+            skin_index,
+            focused: Some(MenuField::PlayerName),
+        }
+    }
+
+    pub fn player_name(&self) -> String {
+        let name = self.player_name.trim();
+        if name.is_empty() {
+            return settings::DEFAULT_PLAYER_NAME.to_string();
+        }
+
+        return name.to_string();
+    }
+
+    pub fn color(&self) -> Option<PlayerColor> {
+        self.color_index.map(|index| settings::PLAYER_COLORS[index])
+    }
+
+    pub fn skin(&self) -> Option<String> {
+        self.skin_index.map(|index| self.skins[index].clone())
+    }
+
+    fn cycle_skin(&mut self, forward: bool) {
+        let options = self.skins.len() + 1;
+        // 0 is no skin, the skins start at 1
+        let current = self.skin_index.map(|index| index + 1).unwrap_or(0);
+        let next = if forward {
+            (current + 1) % options
+        } else {
+            (current + options - 1) % options
+        };
+
+        self.skin_index = if next == 0 { None } else { Some(next - 1) };
+    }
+
+    fn cycle_color(&mut self, forward: bool) {
+        let options = settings::PLAYER_COLORS.len() + 1;
+        // 0 is the automatic color, the palette starts at 1
+        let current = self.color_index.map(|index| index + 1).unwrap_or(0);
+        let next = if forward {
+            (current + 1) % options
+        } else {
+            (current + options - 1) % options
+        };
+
+        self.color_index = if next == 0 { None } else { Some(next - 1) };
+    }
+
+    fn cycle_focus(&mut self) {
+        self.focused = match self.focused {
+            Some(MenuField::PlayerName) => Some(MenuField::ServerAddress),
+            _ => Some(MenuField::PlayerName),
+        };
+    }
+
+    fn focused_text(&mut self) -> Option<(&mut String, usize)> {
+        match self.focused {
+            Some(MenuField::PlayerName) => Some((&mut self.player_name, settings::MAX_PLAYER_NAME_LENGTH)),
+            Some(MenuField::ServerAddress) => Some((&mut self.server_address, settings::MAX_SERVER_ADDRESS_LENGTH)),
+            None => None,
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event, layout: &MenuLayout) -> Option<MenuAction> {
+        match event {
+            Event::Quit { .. } => {
+                return Some(MenuAction::Quit);
+            }
+            Event::TextInput { text, .. } => {
+                if let Some((field, max_length)) = self.focused_text() {
+                    for character in text.chars() {
+                        if field.chars().count() >= max_length {
+                            break;
+                        }
+                        field.push(character);
+                    }
+                }
+            }
+            Event::KeyDown { keycode: Some(keycode), .. } => {
+                match *keycode {
+                    Keycode::Backspace => {
+                        if let Some((field, _)) = self.focused_text() {
+                            field.pop();
+                        }
+                    }
+                    Keycode::Tab => self.cycle_focus(),
+                    Keycode::Left => self.cycle_color(false),
+                    Keycode::Right => self.cycle_color(true),
+                    Keycode::Up => self.cycle_skin(false),
+                    Keycode::Down => self.cycle_skin(true),
+                    Keycode::Return | Keycode::KpEnter => return Some(MenuAction::Play),
+                    Keycode::Escape => return Some(MenuAction::Quit),
+                    _ => {}
+                }
+            }
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                let point = Point::new(*x, *y);
+
+                if layout.play.contains_point(point) {
+                    return Some(MenuAction::Play);
+                }
+                if layout.quit.contains_point(point) {
+                    return Some(MenuAction::Quit);
+                }
+                if layout.previous_skin.contains_point(point) {
+                    self.cycle_skin(false);
+                    return None;
+                }
+                if layout.next_skin.contains_point(point) {
+                    self.cycle_skin(true);
+                    return None;
+                }
+
+                for (color_index, swatch) in layout.colors.iter() {
+                    if swatch.contains_point(point) {
+                        self.color_index = *color_index;
+                        return None;
+                    }
+                }
+
+                self.focused = if layout.player_name.contains_point(point) {
+                    Some(MenuField::PlayerName)
+                } else if layout.server_address.contains_point(point) {
+                    Some(MenuField::ServerAddress)
+                } else {
+                    None
+                };
+            }
+            _ => {}
+        }
+
+        return None;
+    }
+}
+
+impl Ui {
+    // Shows the main menu until the player either presses Play or quits,
+    // the latest game state keeps being drawn behind it
+    pub fn menu(
+        &mut self,
+        menu: &mut Menu,
+        rx: &Receiver<G2UMessage>,
+        font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<MenuAction, UiError> {
+        let text_input = self.video_subsystem.text_input();
+        text_input.start();
+
+        let mut background: Option<Game> = None;
+
+        loop {
+            let frame_timestamp = time::now();
+
+            loop {
+                match rx.try_recv() {
+                    Ok(G2UMessage::StateUpdate(game)) => background = Some(*game),
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Err(TransportError::GameDisconnected.into()),
+                }
+            }
+
+            let layout = self.menu_layout()?;
+
+            let events: Vec<Event> = self.event_pump.poll_iter().collect();
+            for event in events {
+                if let Event::Window {
+                    win_event: WindowEvent::Resized(width, height),
+                    ..
+                } = event {
+                    self.resize(width as u32, height as u32);
+                }
+
+                if let Some(action) = menu.handle_event(&event, &layout) {
+                    text_input.stop();
+                    return Ok(action);
+                }
+            }
+
+            self.draw_background();
+            if let Some(game) = background.as_mut() {
+                self.draw_fruits(game)?;
+            }
+            self.draw_menu(menu, &layout, font, texture_creator)?;
+            self.canvas.present();
+
+            Game::delay_fps(frame_timestamp, settings::MAX_UI_FPS);
+        }
+    }
+
+    fn menu_layout(&self) -> Result<MenuLayout, UiError> {
+        let (width, height) = self.canvas.output_size()?;
+        let center_x = width as i32 / 2;
+        let top = height as i32 / 2 - 200;
+
+        let swatches = settings::PLAYER_COLORS.len() as u32 + 1;
+        let swatches_width = swatches * SWATCH_SIZE + (swatches - 1) * SWATCH_GAP;
+        let colors = (0..swatches)
+            .map(|swatch| {
+                let color_index = if swatch == 0 { None } else { Some(swatch as usize - 1) };
+                let rect = Rect::new(
+                    center_x - swatches_width as i32 / 2 + (swatch * (SWATCH_SIZE + SWATCH_GAP)) as i32,
+                    top + 150,
+                    SWATCH_SIZE,
+                    SWATCH_SIZE,
+                );
+                (color_index, rect)
+            })
+            .collect();
+
+        return Ok(MenuLayout {
+            title: Position {
+                x: center_x as f32,
+                y: top as f32,
+            },
+            player_name: Rect::new(center_x - FIELD_WIDTH as i32 / 2, top + 80, FIELD_WIDTH, FIELD_HEIGHT),
+            colors,
+            skin: Rect::new(center_x - FIELD_WIDTH as i32 / 2, top + 225, FIELD_WIDTH, FIELD_HEIGHT),
+            previous_skin: Rect::new(center_x - FIELD_WIDTH as i32 / 2, top + 225, FIELD_HEIGHT, FIELD_HEIGHT),
+            next_skin: Rect::new(center_x + FIELD_WIDTH as i32 / 2 - FIELD_HEIGHT as i32, top + 225, FIELD_HEIGHT, FIELD_HEIGHT),
+            server_address: Rect::new(center_x - FIELD_WIDTH as i32 / 2, top + 300, FIELD_WIDTH, FIELD_HEIGHT),
+            play: Rect::new(center_x - BUTTON_WIDTH as i32 - 10, top + 370, BUTTON_WIDTH, BUTTON_HEIGHT),
+            quit: Rect::new(center_x + 10, top + 370, BUTTON_WIDTH, BUTTON_HEIGHT),
+        });
+    }
+
+    fn draw_menu(
+        &mut self,
+        menu: &Menu,
+        layout: &MenuLayout,
+        font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        self.draw_overlay()?;
+
+        self.write_centered_text(settings::WINDOW_TITLE, Color::WHITE, layout.title, font, texture_creator)?;
+
+        self.write_label("Nickname", layout.player_name, font, texture_creator)?;
+        self.draw_text_field(
+            &menu.player_name,
+            settings::DEFAULT_PLAYER_NAME,
+            layout.player_name,
+            menu.focused == Some(MenuField::PlayerName),
+            font,
+            texture_creator,
+        )?;
+
+        let colors_top = layout.colors[0].1;
+        self.write_label("Color", Rect::new(layout.player_name.x(), colors_top.y(), 0, 0), font, texture_creator)?;
+        for (color_index, swatch) in layout.colors.iter() {
+            let color = match color_index {
+                Some(index) => {
+                    let color = settings::PLAYER_COLORS[*index];
+                    Color::RGB(color.r, color.g, color.b)
+                }
+                None => Color::GRAY,
+            };
+
+            self.canvas.set_draw_color(color);
+            self.canvas.fill_rect(*swatch)?;
+
+            if color_index.is_none() {
+                self.write_centered_text("?", Color::BLACK, rect_center(*swatch), font, texture_creator)?;
+            }
+
+            if *color_index == menu.color_index {
+                self.canvas.set_draw_color(Color::WHITE);
+                self.canvas.draw_rect(Rect::new(
+                    swatch.x() - 3,
+                    swatch.y() - 3,
+                    swatch.width() + 6,
+                    swatch.height() + 6,
+                ))?;
+            }
+        }
+
+        self.write_label("Skin", layout.skin, font, texture_creator)?;
+        self.canvas.set_draw_color(Color::RGB(20, 20, 50));
+        self.canvas.fill_rect(layout.skin)?;
+        self.canvas.set_draw_color(Color::GRAY);
+        self.canvas.draw_rect(layout.skin)?;
+        let skin = menu.skin().unwrap_or_else(|| String::from("None"));
+        self.write_centered_text(&skin, Color::WHITE, rect_center(layout.skin), font, texture_creator)?;
+        self.draw_button("<", layout.previous_skin, Color::RGB(40, 40, 90), font, texture_creator)?;
+        self.draw_button(">", layout.next_skin, Color::RGB(40, 40, 90), font, texture_creator)?;
+
+        self.write_label("Server", layout.server_address, font, texture_creator)?;
+        self.draw_text_field(
+            &menu.server_address,
+            "local",
+            layout.server_address,
+            menu.focused == Some(MenuField::ServerAddress),
+            font,
+            texture_creator,
+        )?;
+
+        self.draw_button("Play", layout.play, Color::RGB(40, 160, 70), font, texture_creator)?;
+        self.draw_button("Quit", layout.quit, Color::RGB(160, 50, 50), font, texture_creator)?;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,708 @@
+use sdl2::{Sdl, VideoSubsystem, EventPump};
+use sdl2::video::Window;
+use sdl2::render::Canvas;
+use sdl2::render;
+use sdl2::image::{InitFlag};
+use sdl2::pixels::Color;
+use sdl2::render::Texture;
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+
+use sdl2::gfx::primitives::DrawRenderer;
+
+use sdl2::rect::Rect;
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::{Sender, Receiver, TryRecvError};
+
+use agario::game::events::TickEvents;
+use agario::game::ids::PlayerId;
+use std::path::Path;
+
+use agario::helper::{G2UMessage, U2GMessage, PlayerEvent};
+use agario::time;
+use agario::logging::{self, Level};
+use agario::{info, debug};
+use agario::geometry;
+use geometry::{Position, rectangle::{Rectangle, Size, RectangleSize}, circle::Circle};
+use agario::game;
+
+use error::UiError;
+
+use game::map::MapView;
+use game::player::{Cell, JoinRequest, Player};
+
+use game::{Game};
+
+// Mods
+pub mod settings;
+pub mod error;
+pub mod chat;
+pub mod console;
+pub mod debug;
+pub mod feed;
+pub mod fonts;
+pub mod input;
+pub mod inspector;
+pub mod interpolation;
+pub mod menu;
+pub mod pause;
+pub mod skins;
+pub mod widgets;
+
+use chat::Chat;
+use console::Console;
+use feed::Feed;
+use debug::DebugStats;
+use fonts::FontCache;
+use input::{Bindings, Input};
+use inspector::Selection;
+use interpolation::Interpolator;
+use menu::{Menu, MenuAction};
+use pause::PauseMenu;
+use skins::Skins;
+
+type TextureCreator = sdl2::render::TextureCreator<sdl2::video::WindowContext>;
+
+pub struct UiSettings {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub bindings: Bindings,
+}
+
+#[derive(Clone)]
+pub struct DebugOptions {
+    pub game_state: bool,
+    pub map_view: bool,
+    // Draw the states exactly as received instead of interpolating between them
+    pub raw_states: bool,
+    pub timings: bool,
+    pub entities: bool,
+    pub frame_graph: bool,
+    pub collision_shapes: bool,
+    pub vectors: bool,
+    pub spatial_grid: bool,
+    // Also shrinks the culling area so its edges can be seen on screen
+    pub culling: bool,
+}
+
+pub struct Ui {
+    pub sdl_context: Sdl,
+    pub video_subsystem: VideoSubsystem,
+    pub canvas: Canvas<Window>,
+    pub event_pump: EventPump,
+    pub input: Input,
+    pub map_view: MapView,
+    pub player_name: String,
+    pub player_color: Option<game::player::PlayerColor>,
+    pub player_skin: Option<String>,
+    pub server_address: String,
+    // Set once the game answered the join request
+    pub player_id: Option<PlayerId>,
+    pub debug_options: DebugOptions,
+    pub debugging: bool,
+    pub debug_stats: DebugStats,
+    pub selection: Option<Selection>,
+    pub pause_menu: Option<PauseMenu>,
+    pub console: Console,
+    pub chat: Chat,
+    pub feed: Feed,
+    pub show_mass: bool,
+    pub fps: u16,
+}
+
+impl Ui {
+    pub fn new(ui_settings: UiSettings) -> Result<Ui, UiError> {
+        let sdl_context = sdl2::init()?;
+        let video_subsystem = sdl_context.video()?;
+        let _image_context = sdl2::image::init(InitFlag::PNG);
+
+        let window = video_subsystem
+            .window(&ui_settings.title, ui_settings.width, ui_settings.height)
+            .opengl()
+            .resizable()
+            .position_centered()
+            .build()?;
+
+        let mut canvas_builder = window.into_canvas();
+        if settings::VSYNC {
+            canvas_builder = canvas_builder.present_vsync();
+        }
+        let canvas = canvas_builder.build()?;
+
+        let event_pump = sdl_context.event_pump()?;
+
+        let input = Input::new(ui_settings.bindings, sdl_context.game_controller().ok());
+
+        let map_view = MapView {
+            position: Position {
+                x: settings::MAP_WIDTH as f32 / 2.0,
+                y: settings::MAP_HEIGHT as f32 / 2.0,
+            },
+            size: Size::Rectangle(RectangleSize {
+                width: settings::WINDOW_WIDTH,
+                height: settings::WINDOW_HEIGHT,
+            }),
+        };
+
+        return Ok(Ui {
+            sdl_context,
+            video_subsystem,
+            canvas,
+            event_pump,
+            input,
+            map_view,
+
+            player_name: String::new(),
+            player_color: None,
+            player_skin: None,
+            server_address: String::new(),
+            player_id: None,
+
+            debug_options: settings::DEFAULT_DEBUG_OPTIONS,
+            debugging: settings::DEFAULT_DEBUGGING_STATE,
+            debug_stats: DebugStats::new(),
+            selection: None,
+            pause_menu: None,
+            console: Console::default(),
+            chat: Chat::default(),
+            feed: Feed::default(),
+            show_mass: settings::DEFAULT_SHOW_MASS,
+            fps: 0,
+        });
+    }
+
+    // The game answers with G2UMessage::Joined, until then there's no player to steer
+    fn join(&mut self, tx: &Sender<U2GMessage>) -> Result<(), UiError> {
+        info!("joining", name = self.player_name, server = self.server_address);
+        tx.send(U2GMessage::Join(JoinRequest {
+            name: self.player_name.clone(),
+            color: self.player_color,
+            skin: self.player_skin.clone(),
+            bot: false,
+        }))?;
+
+        return Ok(());
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.map_view.size = Size::Rectangle(RectangleSize {
+                width,
+                height,
+            }
+        );
+    }
+
+    fn inputs(&mut self, tx: &Sender<U2GMessage>, game: Option<&Game>) -> Result<(), UiError> {
+        let mut events = vec![];
+        // Steering is sent at most once per frame, however many events moved it
+        let mut steering_changed = false;
+        let polled_events: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in polled_events {
+            if self.console_events(&event, tx)? || self.chat_events(&event, tx)? {
+                continue;
+            }
+
+            if self.pause_events(&event, tx)? {
+                continue;
+            }
+
+            if self.input.handle_event(&event) {
+                steering_changed = true;
+            }
+
+            match event {
+                Event::Window {
+                    win_event: WindowEvent::Resized(width, height),
+                    ..
+                } => {
+                    self.resize(width as u32, height as u32);
+                }
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => {
+                    self.toggle_pause(tx)?;
+                }
+
+                Event::Quit {
+                    ..
+                } => {
+                    tx.send(U2GMessage::Quit)?;
+                }
+                _ => {
+                    events.push(event.clone());
+                }
+            }
+        }
+
+        for event in events {
+            self.debug_events(&event);
+            self.inspector_events(&event, game, tx)?;
+        }
+
+        if steering_changed {
+            self.send_steering(tx, game)?;
+        }
+
+        let actions = self.input.take_actions();
+        if let (Some(player_id), None) = (&self.player_id, &self.pause_menu) {
+            for action in actions {
+                tx.send(U2GMessage::PlayerEvent(*player_id, action))?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn send_steering(&mut self, tx: &Sender<U2GMessage>, game: Option<&Game>) -> Result<(), UiError> {
+        let game = match (game, &self.pause_menu) {
+            (Some(game), None) => game,
+            _ => return Ok(()),
+        };
+
+        let player = match Player::get(self.player_id, game) {
+            Some(player) => player,
+            None => return Ok(()),
+        };
+
+        let cell = match player.position(&game.map.world) {
+            Some(position) => self.map_view.map_position(position),
+            None => return Ok(()),
+        };
+
+        if let Some(direction) = self.input.steering(cell) {
+            tx.send(
+                U2GMessage::PlayerEvent(
+                    player.id,
+                    PlayerEvent::Moving(direction)
+                )
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    fn write_text(
+        &mut self,
+        text: &str,
+        color: Color,
+        position: Position,
+        font: &Font,
+        texture_creator: &TextureCreator,
+        line_height: Option<u16>,
+    ) -> Result<(), UiError> {
+        let text_lines: Vec<&str> = text.split("\n").filter(|line| line.len() > 0).collect();
+        let line_height = line_height.unwrap_or(15);
+
+        for (line_i, &line) in text_lines.iter().enumerate() {
+            let surface = font
+                .render(line)
+                .blended(color)?;
+            let texture = texture_creator
+                .create_texture_from_surface(&surface)?;
+
+            let render::TextureQuery { width, height, .. } = texture.query();
+        
+            let target = Rect::new(
+                position.x as i32,
+                position.y as i32 + line_height as i32 * line_i as i32,
+                width,
+                height
+            );
+            self.canvas.copy(&texture, None, Some(target))?;
+
+        }
+
+        return Ok(());
+    }
+
+    fn draw_sprite(
+        &mut self,
+        texture: &Texture,
+        sprite_rectangle: Rectangle,
+        target_rectangle: Rectangle,
+    ) -> Result<(), UiError> {
+        let sprite_rectangle_size = Rectangle::to_rectangle_size(sprite_rectangle.size);
+        
+        let target_corners = target_rectangle.get_corners();
+        let target_rectangle_size = Rectangle::to_rectangle_size(target_rectangle.size);
+
+        self.canvas.copy(
+            texture,
+            Some(
+                Rect::new(
+                    sprite_rectangle.position.x as i32,
+                    sprite_rectangle.position.y as i32,
+                    sprite_rectangle_size.width,
+                    sprite_rectangle_size.height,
+                )
+            ),
+            Some(
+                Rect::new(
+                    target_corners.top_left.x as i32,
+                    target_corners.top_left.y as i32,
+                    target_rectangle_size.width,
+                    target_rectangle_size.height,
+                )
+            )
+        )?;
+
+        return Ok(());
+    }
+
+    fn draw_background(&mut self) {
+        self.canvas.set_draw_color(Color::RGB(0, 0, 32));
+        self.canvas.clear();
+    }
+
+    fn circle(&mut self, circle: geometry::circle::Circle, color: Color, filled: bool) -> Result<(), UiError> {
+        if filled {
+            DrawRenderer::filled_circle(
+                &self.canvas,
+                circle.center.x as i16,
+                circle.center.y as i16,
+                circle.radius as i16,
+                color,
+            )?;
+        } else {
+            DrawRenderer::aa_circle(
+                &self.canvas,
+                circle.center.x as i16,
+                circle.center.y as i16,
+                circle.radius as i16,
+                color,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    fn draw_fruits(&mut self, game: &mut game::Game) -> Result<(), UiError> {
+        let fruits = self.culling_view().get_visible_fruits(&game.map);
+        for fruit in fruits {
+            let mapped_pos = self.map_view.map_position(fruit.center);
+            
+            let (r, g, b) = (
+                (fruit.center.x % 200.0) as u8,
+                (fruit.center.y % 200.0) as u8,
+                ((fruit.center.x * fruit.center.y) % 255.0) as u8,
+            );
+            self.circle(
+                Circle {
+                    center: mapped_pos,
+                    ..fruit
+                },
+                Color::RGB(r, g, b),
+                true
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    fn draw_skin(&mut self, texture: &Texture, body_part: Circle) -> Result<(), UiError> {
+        let render::TextureQuery { width, height, .. } = texture.query();
+        // Skins are clipped to the circle inscribed in the picture's centered square
+        let side = width.min(height);
+
+        self.draw_sprite(
+            texture,
+            Rectangle {
+                position: Position {
+                    x: ((width - side) / 2) as f32,
+                    y: ((height - side) / 2) as f32,
+                },
+                size: Size::Square(side),
+            },
+            Rectangle {
+                position: body_part.center,
+                size: Size::Square(body_part.radius * 2),
+            },
+        )
+    }
+
+    fn draw_players(
+        &mut self,
+        game: &mut game::Game,
+        skins: &Skins,
+        label_fonts: &mut FontCache,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        let culling_view = self.culling_view();
+        let mut players: Vec<(Player, Vec<Cell>)> = game.map.players
+            .values()
+            .filter(|player| culling_view.is_player_visible(player, &game.map))
+            .map(|player| (player.clone(), self.map_view.map_player(player, &game.map)))
+            .collect();
+
+        // Paused players are hidden from everyone but themselves
+        if let Some(player) = Player::get(self.player_id, game) {
+            if player.paused {
+                players.push((player.clone(), self.map_view.map_player(player, &game.map)));
+            }
+        }
+
+        // Bigger players are drawn last so they cover the smaller ones
+        players.sort_by_key(|(player, _)| player.get_score(&game.map.world));
+
+        for (player, body_parts) in players {
+            self.draw_player(&player, &body_parts, skins, label_fonts, texture_creator)?;
+        }
+
+        return Ok(());
+    }
+
+    // Expects the player's body parts already mapped to the screen
+    fn draw_player(
+        &mut self,
+        player: &Player,
+        body_parts: &[Cell],
+        skins: &Skins,
+        label_fonts: &mut FontCache,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        let color = Color::RGB(player.color.r, player.color.g, player.color.b);
+
+        for body_part in body_parts.iter() {
+            self.circle(
+                body_part.circle,
+                color,
+                true,
+            )?;
+
+            // Missing skins fall back to the plain color
+            if let Some(texture) = player.skin.as_ref().and_then(|skin| skins.get(skin)) {
+                self.draw_skin(texture, body_part.circle)?;
+            }
+
+            // Too small to be read anyway
+            if body_part.radius < settings::MIN_LABEL_RADIUS {
+                continue;
+            }
+
+            let name_size = (body_part.radius as f32 * settings::LABEL_FONT_SCALE)
+                .min(settings::MAX_LABEL_FONT_SIZE as f32) as u16;
+            self.draw_label(&player.name, body_part.center, name_size, label_fonts, texture_creator)?;
+
+            if self.show_mass {
+                let mass_size = name_size / 2;
+                self.draw_label(
+                    &Player::mass(&body_part.circle).to_string(),
+                    Position {
+                        x: body_part.center.x,
+                        y: body_part.center.y + (name_size + mass_size) as f32 / 2.0,
+                    },
+                    mass_size,
+                    label_fonts,
+                    texture_creator,
+                )?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // White text with a dark outline, readable over any cell color or skin
+    fn draw_label(
+        &mut self,
+        text: &str,
+        center: Position,
+        point_size: u16,
+        label_fonts: &mut FontCache,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        let outline = (point_size / 10).max(1);
+
+        for (outline, color) in [(outline, Color::BLACK), (0, Color::WHITE)] {
+            if let Some(font) = label_fonts.get(point_size, outline) {
+                self.write_centered_text(text, color, center, font, texture_creator)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn draw(
+        &mut self,
+        game: &mut game::Game,
+        skins: &Skins,
+        label_fonts: &mut FontCache,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        self.draw_fruits(game)?;
+        self.draw_players(game, skins, label_fonts, texture_creator)?;
+
+        return Ok(());
+    }
+
+    fn load_font<'ttf>(
+        ttf_context: &'ttf sdl2::ttf::Sdl2TtfContext,
+        path: &str,
+        point_size: u16,
+    ) -> Result<Font<'ttf, 'static>, UiError> {
+        ttf_context
+            .load_font(Path::new(path), point_size)
+            .map_err(|message| UiError::Font {
+                path: path.to_string(),
+                message,
+            })
+    }
+
+    // Returns once the player quits, or with an error when the game went away
+    pub fn run(
+        &mut self,
+        tx: &Sender<U2GMessage>,
+        rx: &Receiver<G2UMessage>,
+        events: &Receiver<TickEvents>,
+    ) -> Result<(), UiError> {
+        let _span = logging::span(Level::Info, "ui");
+        let ttf_context = sdl2::ttf::init()?;
+        let texture_creator = self.canvas.texture_creator();
+
+        // Load Skins
+        let skins = Skins::load(settings::SPRITES_FOLDER_PATH, &texture_creator);
+
+        // Load debug font
+        let mut debug_font = Ui::load_font(&ttf_context, settings::DEBUG_FONT_PATH, settings::DEBUG_FONT_POINT_SIZE)?;
+        debug_font.set_style(sdl2::ttf::FontStyle::NORMAL);
+
+        // Game font, loaded at whatever size the labels need
+        let mut label_fonts = FontCache::new(&ttf_context, settings::GAME_FONT_PATH, settings::LABEL_FONT_SIZE_STEP);
+
+        // Load Menu font
+        let menu_font = Ui::load_font(&ttf_context, settings::GAME_FONT_PATH, settings::MENU_FONT_POINT_SIZE)?;
+
+        let (width, height) = self.canvas.output_size()?;
+        info!("ui started", width = width, height = height, vsync = settings::VSYNC, skins = skins.names().len());
+
+        let mut menu = Menu::new(
+            &self.player_name,
+            self.player_color,
+            self.player_skin.clone(),
+            skins.names(),
+            &self.server_address,
+        );
+        match self.menu(&mut menu, rx, &menu_font, &texture_creator)? {
+            MenuAction::Play => {
+                self.player_name = menu.player_name();
+                self.player_color = menu.color();
+                self.player_skin = menu.skin();
+                self.server_address = menu.server_address.clone();
+            }
+            MenuAction::Quit => {
+                info!("quit from the main menu");
+                tx.send(U2GMessage::Quit)?;
+                return Ok(());
+            }
+        }
+
+        if self.player_id.is_none() {
+            self.join(tx)?;
+        }
+
+        // Latest-value mailbox, only the last two ticks are kept for interpolation
+        let mut interpolator = Interpolator::new();
+
+        'frame_loop: loop {
+            let frame_timestamp = time::now();
+
+            let mut queue_depth = 0;
+            loop {
+                match rx.try_recv() {
+                    Ok(G2UMessage::StateUpdate(game)) => {
+                        interpolator.push(*game);
+                        queue_depth += 1;
+                    }
+                    Ok(G2UMessage::CommandOutput(_, text)) => {
+                        self.console.print(&text);
+                    }
+                    Ok(G2UMessage::Chat(message)) => {
+                        self.chat.receive(message);
+                    }
+                    Ok(G2UMessage::Joined(player_id)) => {
+                        debug!("joined", player_id = player_id);
+                        self.player_id = Some(player_id);
+                    }
+                    Ok(G2UMessage::ChatNotice(player_id, text)) => {
+                        if self.player_id == Some(player_id) {
+                            self.chat.notice(&text);
+                        }
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    // The game is gone, either because the player quit or because it failed
+                    Err(TryRecvError::Disconnected) => {
+                        debug!("game disconnected, closing the window");
+                        break 'frame_loop;
+                    }
+                }
+            }
+
+            self.debug_stats.queue_depth = queue_depth;
+
+            // The game going away is already noticed above
+            while let Ok(tick_events) = events.try_recv() {
+                self.receive_events(tick_events, tx)?;
+            }
+
+            self.inputs(tx, interpolator.latest())?;
+
+            let render_timestamp = time::now();
+            self.draw_background();
+
+            let mut game = match self.debug_options.raw_states {
+                true => interpolator.latest().cloned(),
+                false => interpolator.at(frame_timestamp),
+            };
+
+            if let Some(game) = game.as_mut() {
+                let player_pos = match Player::get(self.player_id, game).and_then(|player| player.position(&game.map.world)) {
+                    Some(position) => position,
+                    None => Position {
+                        x: settings::MAP_WIDTH as f32 / 2.0,
+                        y: settings::MAP_HEIGHT as f32 / 2.0,
+                    }
+                };
+
+                self.map_view.position = player_pos;
+
+                self.draw(game, &skins, &mut label_fonts, &texture_creator)?;
+                self.draw_effects()?;
+
+                if self.debugging {
+                    self.debug(
+                        game,
+                        &debug_font,
+                        &texture_creator,
+                    )?;
+                }
+            }
+
+            self.draw_chat(&debug_font, &texture_creator)?;
+            self.draw_feed(&debug_font, &texture_creator)?;
+            self.draw_pause_menu(&menu_font, &texture_creator)?;
+
+            // Above the pause overlay, stepping through ticks happens while paused
+            if let Some(game) = game.as_ref() {
+                self.draw_inspector(game, &debug_font, &texture_creator)?;
+            }
+
+            self.draw_console(&debug_font, &texture_creator)?;
+
+            self.canvas.present();
+            self.debug_stats.render_time = time::now() - render_timestamp;
+
+            // With vsync on, present() already waits for the display
+            if !settings::VSYNC {
+                Game::delay_fps(frame_timestamp, settings::MAX_UI_FPS);
+            }
+            self.fps = Game::get_fps(frame_timestamp);
+            self.debug_stats.push_frame_time(time::now() - frame_timestamp);
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,179 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::ttf::Font;
+
+use std::sync::mpsc::Sender;
+
+use agario::helper::U2GMessage;
+use super::error::UiError;
+use agario::geometry::Position;
+
+use super::{Ui, TextureCreator};
+use super::widgets::{BUTTON_WIDTH, BUTTON_HEIGHT};
+
+const BUTTON_GAP: i32 = 15;
+
+pub struct PauseMenu {
+    pub settings_open: bool,
+}
+
+enum PauseAction {
+    Resume,
+    OpenSettings,
+    CloseSettings,
+    ToggleDebugging,
+    ToggleMass,
+    Quit,
+}
+
+struct PauseLayout {
+    title: Position,
+    buttons: Vec<(&'static str, PauseAction, Rect)>,
+}
+
+impl Ui {
+    pub fn toggle_pause(&mut self, tx: &Sender<U2GMessage>) -> Result<(), UiError> {
+        let player_id = match self.player_id {
+            Some(player_id) => player_id,
+            None => return Ok(()),
+        };
+
+        match self.pause_menu {
+            Some(_) => {
+                self.pause_menu = None;
+                tx.send(U2GMessage::Resume(player_id))?;
+            }
+            None => {
+                self.pause_menu = Some(PauseMenu {
+                    settings_open: false,
+                });
+                tx.send(U2GMessage::Pause(player_id))?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Only does anything while paused: Escape resumes or leaves the settings, and clicks run the
+    // buttons. Clicks next to them fall through to the entity inspector
+    pub fn pause_events(&mut self, event: &Event, tx: &Sender<U2GMessage>) -> Result<bool, UiError> {
+        let settings_open = match &self.pause_menu {
+            Some(pause_menu) => pause_menu.settings_open,
+            None => return Ok(false),
+        };
+
+        let action = match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                if settings_open {
+                    PauseAction::CloseSettings
+                } else {
+                    PauseAction::Resume
+                }
+            }
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                let point = Point::new(*x, *y);
+                let clicked = self.pause_layout(settings_open)?
+                    .buttons
+                    .into_iter()
+                    .find(|(_, _, rect)| rect.contains_point(point));
+
+                match clicked {
+                    Some((_, action, _)) => action,
+                    // Lets the entity inspector pick things behind the overlay
+                    None => return Ok(false),
+                }
+            }
+            Event::MouseMotion { .. } => return Ok(true),
+            _ => return Ok(false),
+        };
+
+        match action {
+            PauseAction::Resume => self.toggle_pause(tx)?,
+            PauseAction::OpenSettings | PauseAction::CloseSettings => {
+                self.pause_menu = Some(PauseMenu {
+                    settings_open: matches!(action, PauseAction::OpenSettings),
+                });
+            }
+            PauseAction::ToggleDebugging => {
+                self.debugging = !self.debugging;
+            }
+            PauseAction::ToggleMass => {
+                self.show_mass = !self.show_mass;
+            }
+            PauseAction::Quit => {
+                tx.send(U2GMessage::Quit)?;
+            }
+        }
+
+        return Ok(true);
+    }
+
+    fn pause_layout(&self, settings_open: bool) -> Result<PauseLayout, UiError> {
+        let (width, height) = self.canvas.output_size()?;
+        let center_x = width as i32 / 2;
+        let top = height as i32 / 2 - 120;
+
+        let buttons = if settings_open {
+            vec![
+                (if self.debugging { "Debug: On" } else { "Debug: Off" }, PauseAction::ToggleDebugging),
+                (if self.show_mass { "Mass: On" } else { "Mass: Off" }, PauseAction::ToggleMass),
+                ("Back", PauseAction::CloseSettings),
+            ]
+        } else {
+            vec![
+                ("Resume", PauseAction::Resume),
+                ("Settings", PauseAction::OpenSettings),
+                ("Quit", PauseAction::Quit),
+            ]
+        };
+
+        let buttons = buttons
+            .into_iter()
+            .enumerate()
+            .map(|(button_i, (label, action))| {
+                let rect = Rect::new(
+                    center_x - BUTTON_WIDTH as i32 / 2,
+                    top + 60 + button_i as i32 * (BUTTON_HEIGHT as i32 + BUTTON_GAP),
+                    BUTTON_WIDTH,
+                    BUTTON_HEIGHT,
+                );
+                (label, action, rect)
+            })
+            .collect();
+
+        return Ok(PauseLayout {
+            title: Position {
+                x: center_x as f32,
+                y: top as f32,
+            },
+            buttons,
+        });
+    }
+
+    pub fn draw_pause_menu(&mut self, font: &Font, texture_creator: &TextureCreator) -> Result<(), UiError> {
+        let settings_open = match &self.pause_menu {
+            Some(pause_menu) => pause_menu.settings_open,
+            None => return Ok(()),
+        };
+
+        let layout = self.pause_layout(settings_open)?;
+
+        self.draw_overlay()?;
+        self.write_centered_text(
+            if settings_open { "Settings" } else { "Paused" },
+            Color::WHITE,
+            layout.title,
+            font,
+            texture_creator,
+        )?;
+
+        for (label, _, rect) in layout.buttons {
+            self.draw_button(label, rect, Color::RGB(40, 40, 90), font, texture_creator)?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,71 @@
+use sdl2::pixels::Color;
+
+use super::DebugOptions;
+
+// The client's own settings, on top of the simulation ones from the library
+pub use agario::settings::*;
+
+// Window
+pub const WINDOW_TITLE: &str = "Agar.io";
+pub const WINDOW_WIDTH: u32 = 600;
+pub const WINDOW_HEIGHT: u32 = 600;
+// The UI draws on its own clock, either synced to the display or capped to MAX_UI_FPS
+pub const VSYNC: bool = true;
+pub const MAX_UI_FPS: u16 = 144;
+
+// Console
+pub const CONSOLE_SCROLLBACK: usize = 100;
+pub const CONSOLE_VISIBLE_LINES: usize = 10;
+pub const MAX_CONSOLE_INPUT_LENGTH: usize = 120;
+
+// Chat
+pub const CHAT_SCROLLBACK: usize = 100;
+pub const CHAT_VISIBLE_LINES: usize = 6;
+// Messages stay on screen this long, then fade out over CHAT_FADE_MS
+pub const CHAT_FADE_DELAY_MS: u16 = 8_000;
+pub const CHAT_FADE_MS: u16 = 2_000;
+
+// Kill feed and effects
+pub const KILL_FEED_LINES: usize = 5;
+pub const EFFECT_DURATION_MS: u16 = 400;
+
+// Sprites
+pub const SPRITES_FOLDER_PATH: &str = "./assets/sprites";
+
+// Debugging
+pub const DEFAULT_DEBUGGING_STATE: bool = true;
+pub const DEBUG_FONT_PATH: &str = "./assets/fonts/debug.ttf";
+pub const DEBUG_FONT_POINT_SIZE: u16 = 15;
+pub const DEBUG_COLOR: Color = Color::MAGENTA;
+pub const DEFAULT_DEBUG_OPTIONS: DebugOptions = DebugOptions {
+    game_state: true,
+    map_view: true,
+    raw_states: false,
+    timings: true,
+    entities: true,
+    frame_graph: false,
+    collision_shapes: false,
+    vectors: false,
+    spatial_grid: false,
+    culling: false,
+};
+// How much the view is shrunk on every side while debugging the culling
+pub const DEBUG_CULLING_INSET: u32 = 150;
+// Velocity vectors are drawn as the distance covered in this many seconds
+pub const DEBUG_VELOCITY_SCALE: f32 = 0.5;
+// How far past the latest game state the UI keeps predicting motion
+pub const MAX_EXTRAPOLATION_MS: u16 = 100;
+
+// Font
+pub const GAME_FONT_PATH: &str = "./assets/fonts/game.ttf";
+// Cell labels are sized relative to the cell radius
+pub const LABEL_FONT_SCALE: f32 = 0.5;
+pub const LABEL_FONT_SIZE_STEP: u16 = 4;
+pub const MAX_LABEL_FONT_SIZE: u16 = 96;
+pub const MIN_LABEL_RADIUS: u32 = 16;
+pub const DEFAULT_SHOW_MASS: bool = true;
+
+// Menu
+pub const MENU_FONT_POINT_SIZE: u16 = 20;
+pub const MENU_BACKGROUND_COLOR: Color = Color::RGBA(0, 0, 0, 160);
+pub const MENU_FOCUS_COLOR: Color = Color::RGB(80, 160, 255);
@@ -0,0 +1,101 @@
+use sdl2::image::LoadSurface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{BlendMode, Texture};
+use sdl2::surface::Surface;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use agario::warn;
+
+use super::TextureCreator;
+
+// Every PNG in the sprites folder is a skin named after its file, without the extension
+pub struct Skins<'a> {
+    textures: HashMap<String, Texture<'a>>,
+}
+
+impl<'a> Skins<'a> {
+    pub fn load(folder: &str, texture_creator: &'a TextureCreator) -> Skins<'a> {
+        let mut textures = HashMap::new();
+
+        let entries = match fs::read_dir(Path::new(folder)) {
+            Ok(entries) => entries,
+            Err(error) => {
+                warn!("couldn't read the skins folder", folder = folder, error = error);
+                return Skins { textures };
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let is_png = path
+                .extension()
+                .map(|extension| extension.eq_ignore_ascii_case("png"))
+                .unwrap_or(false);
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) if is_png => name.to_string(),
+                _ => continue,
+            };
+
+            match Skins::load_texture(&path, texture_creator) {
+                Ok(texture) => {
+                    textures.insert(name, texture);
+                }
+                Err(error) => {
+                    warn!("skipping skin", path = path.display(), error = error);
+                }
+            }
+        }
+
+        return Skins { textures };
+    }
+
+    // Loads the image and makes everything outside its inscribed circle transparent
+    fn load_texture(path: &Path, texture_creator: &'a TextureCreator) -> Result<Texture<'a>, String> {
+        let surface = Surface::from_file(path)?;
+        let mut surface = surface.convert_format(PixelFormatEnum::RGBA32)?;
+
+        let (width, height) = (surface.width() as usize, surface.height() as usize);
+        if width == 0 || height == 0 {
+            return Err(String::from("the image is empty"));
+        }
+
+        let pitch = surface.pitch() as usize;
+        let radius = width.min(height) as f32 / 2.0;
+        let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+
+        surface.with_lock_mut(|pixels: &mut [u8]| {
+            for y in 0..height {
+                for x in 0..width {
+                    let dx = x as f32 + 0.5 - center_x;
+                    let dy = y as f32 + 0.5 - center_y;
+
+                    if dx * dx + dy * dy > radius * radius {
+                        // RGBA32 keeps the alpha channel in the 4th byte of every pixel
+                        pixels[y * pitch + x * 4 + 3] = 0;
+                    }
+                }
+            }
+        });
+
+        let mut texture = texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|error| error.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+
+        return Ok(texture);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Texture<'a>> {
+        self.textures.get(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.textures.keys().cloned().collect();
+        names.sort();
+        return names;
+    }
+}
@@ -0,0 +1,119 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+use sdl2::ttf::Font;
+
+use super::settings;
+use super::error::UiError;
+use agario::geometry::Position;
+
+use super::{Ui, TextureCreator};
+
+pub const BUTTON_WIDTH: u32 = 140;
+pub const BUTTON_HEIGHT: u32 = 40;
+
+impl Ui {
+    // Darkens everything drawn so far so the widgets on top stand out
+    pub fn draw_overlay(&mut self) -> Result<(), UiError> {
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(settings::MENU_BACKGROUND_COLOR);
+        self.canvas.fill_rect(None)?;
+        self.canvas.set_blend_mode(BlendMode::None);
+
+        return Ok(());
+    }
+
+    pub fn write_label(&mut self, label: &str, field: Rect, font: &Font, texture_creator: &TextureCreator) -> Result<(), UiError> {
+        self.write_text(
+            label,
+            Color::WHITE,
+            Position {
+                x: field.x() as f32,
+                y: field.y() as f32 - settings::MENU_FONT_POINT_SIZE as f32 - 10.0,
+            },
+            font,
+            texture_creator,
+            None,
+        )
+    }
+
+    pub fn draw_text_field(
+        &mut self,
+        text: &str,
+        placeholder: &str,
+        rect: Rect,
+        focused: bool,
+        font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        self.canvas.set_draw_color(Color::RGB(20, 20, 50));
+        self.canvas.fill_rect(rect)?;
+        self.canvas.set_draw_color(if focused { settings::MENU_FOCUS_COLOR } else { Color::GRAY });
+        self.canvas.draw_rect(rect)?;
+
+        let (text, color) = match (text.is_empty(), focused) {
+            (true, false) => (placeholder.to_string(), Color::GRAY),
+            (_, true) => (format!("{text}_"), Color::WHITE),
+            (false, false) => (text.to_string(), Color::WHITE),
+        };
+
+        let (_, text_height) = font.size_of(&text).unwrap_or((0, 0));
+        self.write_text(
+            &text,
+            color,
+            Position {
+                x: rect.x() as f32 + 8.0,
+                y: rect.center().y() as f32 - text_height as f32 / 2.0,
+            },
+            font,
+            texture_creator,
+            None,
+        )
+    }
+
+    pub fn draw_button(
+        &mut self,
+        label: &str,
+        rect: Rect,
+        color: Color,
+        font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        self.canvas.set_draw_color(color);
+        self.canvas.fill_rect(rect)?;
+        self.canvas.set_draw_color(Color::WHITE);
+        self.canvas.draw_rect(rect)?;
+
+        self.write_centered_text(label, Color::WHITE, rect_center(rect), font, texture_creator)
+    }
+
+    pub fn write_centered_text(
+        &mut self,
+        text: &str,
+        color: Color,
+        center: Position,
+        font: &Font,
+        texture_creator: &TextureCreator,
+    ) -> Result<(), UiError> {
+        let (width, height) = font.size_of(text)?;
+
+        self.write_text(
+            text,
+            color,
+            Position {
+                x: center.x - width as f32 / 2.0,
+                y: center.y - height as f32 / 2.0,
+            },
+            font,
+            texture_creator,
+            None,
+        )
+    }
+}
+
+pub fn rect_center(rect: Rect) -> Position {
+    Position {
+        x: rect.center().x() as f32,
+        y: rect.center().y() as f32,
+    }
+}